@@ -12,7 +12,7 @@ use sdl2::pixels::Color;
 
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::time::{Duration, Instant};
+use std::time::Instant;
 
 struct Position {
     position: Vector2<f32>,
@@ -110,19 +110,21 @@ impl entity_system::System for Move {
         "move"
     }
 
-    fn run(&mut self, _now: Instant) -> entity_system::RefreshPeriod {
+    fn run(
+        &mut self,
+        _now: Instant,
+        _resources: &entity_system::Resources,
+    ) -> entity_system::SystemResult {
         let entity_manager = self.entity_manager.borrow();
-        for entity in entity_manager.iter(&self.query_velocity) {
-            entity_manager.update_component_with::<Position, _>(entity, |position| {
-                let velocity = entity_manager.get_component::<Velocity>(entity);
-
-                position.position.x += velocity.position.x;
-                position.position.y += velocity.position.y;
-                position.angle += velocity.angle;
-            });
-        }
+        entity_manager.for_each_mut::<Position, _>(&self.query_velocity, |entity, position| {
+            let velocity = entity_manager.get_component::<Velocity>(entity);
+
+            position.position.x += velocity.position.x;
+            position.position.y += velocity.position.y;
+            position.angle += velocity.angle;
+        });
 
-        entity_system::RefreshPeriod::At(Instant::now() + Duration::from_millis(20))
+        Ok(entity_system::RefreshPeriod::in_ms(20))
     }
 }
 
@@ -165,7 +167,11 @@ impl entity_system::System for Draw {
         "draw"
     }
 
-    fn run(&mut self, _now: Instant) -> entity_system::RefreshPeriod {
+    fn run(
+        &mut self,
+        _now: Instant,
+        _resources: &entity_system::Resources,
+    ) -> entity_system::SystemResult {
         let entity_manager = self.entity_manager.borrow();
 
         self.canvas.set_draw_color(Color::BLACK);
@@ -224,7 +230,7 @@ impl entity_system::System for Draw {
             }
         }
         self.canvas.present();
-        entity_system::RefreshPeriod::At(Instant::now() + Duration::new(0, 1_000_000_000u32 / 30))
+        Ok(entity_system::RefreshPeriod::in_hz(30.0))
     }
 }
 
@@ -261,15 +267,20 @@ impl entity_system::System for Hit {
         "hit"
     }
 
-    fn run(&mut self, _now: Instant) -> entity_system::RefreshPeriod {
+    fn run(
+        &mut self,
+        _now: Instant,
+        _resources: &entity_system::Resources,
+    ) -> entity_system::SystemResult {
         let entity_manager = self.entity_manager.borrow();
 
         let mut delete_entities = Vec::new();
+        let target_entities = entity_manager.collect(&self.query_target);
         for bullet_entity in entity_manager.iter(&self.query_bullet) {
             let bullet_position = entity_manager
                 .get_component::<Position>(bullet_entity)
                 .position;
-            for target_entity in entity_manager.iter(&self.query_target) {
+            for target_entity in target_entities.iter().copied() {
                 let target_position = entity_manager
                     .get_component::<Position>(target_entity)
                     .position;
@@ -286,7 +297,7 @@ impl entity_system::System for Hit {
             entity_manager.delete_entity(*entity);
         });
 
-        entity_system::RefreshPeriod::EveryTime
+        Ok(entity_system::RefreshPeriod::EveryTime)
     }
 }
 
@@ -339,7 +350,11 @@ impl entity_system::System for Keyboard {
         "keyboard"
     }
 
-    fn run(&mut self, _now: Instant) -> entity_system::RefreshPeriod {
+    fn run(
+        &mut self,
+        _now: Instant,
+        _resources: &entity_system::Resources,
+    ) -> entity_system::SystemResult {
         while let Some(event) = self.event_pump.poll_event() {
             match event {
                 Event::KeyDown {
@@ -401,7 +416,7 @@ impl entity_system::System for Keyboard {
             }
         }
 
-        entity_system::RefreshPeriod::EveryTime
+        Ok(entity_system::RefreshPeriod::EveryTime)
     }
 }
 
@@ -465,9 +480,9 @@ fn main() -> Result<(), String> {
     )));
     system_manager.add_system(Rc::clone(&keyboard_system));
 
-    while !keyboard_system.borrow().quit() {
-        system_manager.update(&event_dispatcher);
-    }
+    system_manager
+        .run_until(&event_dispatcher, || keyboard_system.borrow().quit())
+        .map_err(|errors| errors[0].to_string())?;
 
     Ok(())
 }
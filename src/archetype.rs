@@ -0,0 +1,116 @@
+use crate::entity::Entity;
+use std::any::TypeId;
+use std::collections::HashMap;
+
+///
+/// Entities sharing the same set of component types.
+struct Archetype {
+    types: Vec<TypeId>,
+    entities: Vec<Entity>,
+}
+
+///
+/// Index of entities by set of component types.
+#[derive(Default)]
+pub(crate) struct ArchetypeIndex {
+    archetypes: Vec<Archetype>,
+    ids: HashMap<Vec<TypeId>, usize>,
+    locations: HashMap<Entity, (usize, usize)>,
+}
+
+impl ArchetypeIndex {
+    ///
+    /// Add a component type to an entity.
+    pub fn add(&mut self, entity: Entity, type_id: TypeId) {
+        let mut types = self.types(entity);
+        if let Err(pos) = types.binary_search(&type_id) {
+            types.insert(pos, type_id);
+            self.detach(entity);
+            self.attach(entity, types);
+        }
+    }
+
+    ///
+    /// Remove a component type from an entity.
+    pub fn remove(&mut self, entity: Entity, type_id: TypeId) {
+        let mut types = self.types(entity);
+        if let Ok(pos) = types.binary_search(&type_id) {
+            types.remove(pos);
+            self.detach(entity);
+            self.attach(entity, types);
+        }
+    }
+
+    ///
+    /// Remove an entity from the index.
+    pub fn remove_entity(&mut self, entity: Entity) {
+        self.detach(entity);
+    }
+
+    ///
+    /// Remove an entity from the index, returning its component types.
+    pub fn take_entity(&mut self, entity: Entity) -> Vec<TypeId> {
+        let types = self.types(entity);
+        self.detach(entity);
+        types
+    }
+
+    ///
+    /// Add back an entity removed by `take_entity`.
+    pub fn restore_entity(&mut self, entity: Entity, types: Vec<TypeId>) {
+        self.detach(entity);
+        self.attach(entity, types);
+    }
+
+    ///
+    /// Get entities with (at least) all component types.
+    pub fn entities(&self, types: &[TypeId]) -> Vec<Entity> {
+        self.archetypes
+            .iter()
+            .filter(|archetype| {
+                types
+                    .iter()
+                    .all(|type_id| archetype.types.binary_search(type_id).is_ok())
+            })
+            .flat_map(|archetype| archetype.entities.iter().copied())
+            .collect()
+    }
+
+    fn types(&self, entity: Entity) -> Vec<TypeId> {
+        match self.locations.get(&entity) {
+            Some(&(id, _)) => self.archetypes[id].types.clone(),
+            None => Vec::new(),
+        }
+    }
+
+    fn detach(&mut self, entity: Entity) {
+        if let Some((id, pos)) = self.locations.remove(&entity) {
+            let entities = &mut self.archetypes[id].entities;
+            entities.swap_remove(pos);
+            if let Some(&moved) = entities.get(pos) {
+                self.locations.insert(moved, (id, pos));
+            }
+        }
+    }
+
+    fn attach(&mut self, entity: Entity, types: Vec<TypeId>) {
+        if types.is_empty() {
+            return;
+        }
+        let id = match self.ids.get(&types) {
+            Some(&id) => id,
+            None => {
+                let id = self.archetypes.len();
+                self.archetypes.push(Archetype {
+                    types: types.clone(),
+                    entities: Vec::new(),
+                });
+                self.ids.insert(types, id);
+                id
+            }
+        };
+        let entities = &mut self.archetypes[id].entities;
+        self.locations.insert(entity, (id, entities.len()));
+        entities.push(entity);
+    }
+}
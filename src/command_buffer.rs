@@ -0,0 +1,138 @@
+use crate::entity::Entity;
+use crate::entity_manager::{Component, EntityManager, EntityManagerComponent, StorageAccess};
+
+type Command<EntityManagerComponentType> =
+    Box<dyn FnOnce(&mut EntityManager<EntityManagerComponentType>)>;
+
+///
+/// Structural changes recorded while storages are borrowed (e.g. during iteration),
+/// applied later by [`EntityManager::apply`].
+///
+/// Commands are applied in their recording order.
+///
+/// # Examples
+/// ```rust
+/// # use entity_system::{Component, BasicVecStorage, CommandBuffer, Query, create_entity_manager_component};
+/// #
+/// # #[derive(Default)]
+/// # pub struct Life {
+/// #     pub value: u32,
+/// # }
+/// #
+/// # impl Component for Life {
+/// #     type Storage = BasicVecStorage<Self>;
+/// # }
+/// #
+/// # create_entity_manager_component!(EMC { Life });
+/// # type EntityManager = entity_system::EntityManager<EMC>;
+/// #
+/// let mut entity_manager = EntityManager::new();
+/// let entity = entity_manager.create_entity();
+/// entity_manager.add_component::<Life>(entity);
+///
+/// let mut buffer = CommandBuffer::new();
+/// for entity in entity_manager.iter(&Query::new().check_component::<Life>()) {
+///     if entity_manager.get_component::<Life>(entity).value == 0 {
+///         buffer.delete(entity);
+///         buffer.create(|entity_manager, entity| {
+///             entity_manager.add_component_with::<Life, _>(entity, |life| life.value = 10);
+///         });
+///     }
+/// }
+///
+/// entity_manager.apply(buffer);
+/// let entities: Vec<_> = entity_manager.iter_all().collect();
+/// assert_eq!(entities.len(), 1);
+/// assert_eq!(entity_manager.get_component::<Life>(entities[0]).value, 10);
+/// ```
+pub struct CommandBuffer<EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default,
+{
+    commands: Vec<Command<EntityManagerComponentType>>,
+}
+
+impl<EntityManagerComponentType> CommandBuffer<EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default + 'static,
+{
+    ///
+    /// Create an empty `CommandBuffer`
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+        }
+    }
+
+    ///
+    /// Record an entity creation, `f` is called with the new entity to add its components
+    pub fn create<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut EntityManager<EntityManagerComponentType>, Entity) + 'static,
+    {
+        self.commands.push(Box::new(move |entity_manager| {
+            let entity = entity_manager.create_entity();
+            f(entity_manager, entity);
+        }));
+    }
+
+    ///
+    /// Record an entity deletion, cf [`EntityManager::delete_entity`]
+    pub fn delete(&mut self, entity: Entity) {
+        self.commands.push(Box::new(move |entity_manager| {
+            entity_manager.delete_entity(entity)
+        }));
+    }
+
+    ///
+    /// Record a component addition, cf [`EntityManager::add_component_with`]
+    pub fn add_component_with<T, F>(&mut self, entity: Entity, f: F)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+        F: FnOnce(&mut T) + 'static,
+    {
+        self.commands.push(Box::new(move |entity_manager| {
+            entity_manager.add_component_with(entity, f)
+        }));
+    }
+
+    ///
+    /// Record a component removal, cf [`EntityManager::remove_component`]
+    pub fn remove_component<T>(&mut self, entity: Entity)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+    {
+        self.commands.push(Box::new(move |entity_manager| {
+            entity_manager.remove_component::<T>(entity)
+        }));
+    }
+
+    ///
+    /// Get the number of recorded commands
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    ///
+    /// Check if no command is recorded
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub(crate) fn run(self, entity_manager: &mut EntityManager<EntityManagerComponentType>) {
+        for command in self.commands {
+            command(entity_manager);
+        }
+    }
+}
+
+impl<EntityManagerComponentType> Default for CommandBuffer<EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,21 +1,80 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
 
 ///
 /// Entity type, as seen by the user.
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Entity {
     /// id of Entity
     pub id: u32,
 }
 
 impl Entity {
+    ///
+    /// Invalid `Entity`, never returned by [`EntityAllocator`].
+    ///
+    /// Could be used as placeholder for a not yet assigned `Entity`.
+    pub const INVALID: Entity = Entity { id: u32::MAX };
+
     ///
     /// Create a new `Entity`
     pub fn new(id: u32) -> Self {
         Self { id }
     }
+
+    ///
+    /// Check if `Entity` is not [`Entity::INVALID`]
+    pub fn is_valid(&self) -> bool {
+        *self != Self::INVALID
+    }
+}
+
+///
+/// Weak reference on an [`Entity`], invalidated when the entity is deleted.
+///
+/// Cf [`crate::EntityManager::weak`] and [`crate::EntityManager::upgrade`].
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub struct WeakEntity {
+    entity: Entity,
+    version: u32,
+}
+
+impl WeakEntity {
+    ///
+    /// Get the referenced `Entity`, without checking if it is still alive
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
 }
 
+///
+/// Error returned by [`EntityAllocator::alloc_at`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AllocError {
+    ///
+    /// The `Entity` is already allocated.
+    AlreadyAlive(Entity),
+    ///
+    /// The id is [`Entity::INVALID`].
+    InvalidId,
+    ///
+    /// The capacity limit is reached.
+    LimitReached,
+}
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AllocError::AlreadyAlive(entity) => write!(f, "entity {} already alive", entity.id),
+            AllocError::InvalidId => write!(f, "invalid entity id"),
+            AllocError::LimitReached => write!(f, "no more entity available"),
+        }
+    }
+}
+
+impl Error for AllocError {}
+
 ///
 /// Entity Factory.
 ///
@@ -69,7 +128,11 @@ impl Entity {
 /// ```
 pub struct EntityAllocator {
     next: Entity,
-    free: HashSet<Entity>,
+    free: FreeIds,
+    limit: Option<u32>,
+    versions: HashMap<Entity, u32>,
+    recycle: bool,
+    parked: HashSet<Entity>,
 }
 
 impl EntityAllocator {
@@ -78,37 +141,318 @@ impl EntityAllocator {
     pub fn new() -> Self {
         Self {
             next: Entity::new(0),
-            free: HashSet::new(),
+            free: FreeIds::default(),
+            limit: None,
+            versions: HashMap::new(),
+            recycle: true,
+            parked: HashSet::new(),
         }
     }
 
+    ///
+    /// Create a new `EntityAllocator` with at most `limit` allocated `Entity`,
+    /// parked `Entity` included (cf [`EntityAllocator::park`])
+    ///
+    /// # Example
+    /// ```rust
+    /// use entity_system::EntityAllocator;
+    ///
+    /// let mut allocator = EntityAllocator::with_capacity_limit(1);
+    /// let entity = allocator.try_alloc().unwrap();
+    /// assert!(allocator.try_alloc().is_none());
+    ///
+    /// allocator.free(entity);
+    /// assert!(allocator.try_alloc().is_some());
+    /// ```
+    pub fn with_capacity_limit(limit: u32) -> Self {
+        Self {
+            limit: Some(limit),
+            ..Self::new()
+        }
+    }
+
+    ///
+    /// Enable or disable re-use of freed ids (enabled by default).
+    ///
+    /// When disabled, ids are strictly increasing: useful to trace an `Entity` in logs.
+    pub fn set_recycle(&mut self, enabled: bool) {
+        self.recycle = enabled;
+    }
+
+    ///
+    /// Get the number of allocated `Entity`
+    pub fn len(&self) -> usize {
+        self.next.id as usize - self.free.len() - self.parked.len()
+    }
+
+    ///
+    /// Check if no `Entity` is allocated
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///
+    /// Get the number of live `Entity`, same as [`EntityAllocator::len`].
+    ///
+    /// Compared to [`EntityAllocator::high_water`], it tells when to [`EntityAllocator::compact`].
+    pub fn live_count(&self) -> usize {
+        self.len()
+    }
+
+    ///
+    /// Get the highest id ever handed out + 1 (0 if none), it bounds storage sizes.
+    ///
+    /// It decreases only by [`EntityAllocator::compact`].
+    pub fn high_water(&self) -> u32 {
+        self.next.id
+    }
+
     ///
     /// Alloc a new `Entity`
+    ///
+    /// # Panics
+    ///
+    /// If all ids are allocated or the capacity limit is reached
     pub fn alloc(&mut self) -> Entity {
-        match self.free.iter().next() {
-            Some(&value) => {
+        match self.try_alloc() {
+            Some(entity) => entity,
+            None => panic!("no more entity available"),
+        }
+    }
+
+    ///
+    /// Alloc a new `Entity`
+    ///
+    /// # Return
+    ///
+    /// `None` if all ids are allocated or the capacity limit is reached
+    pub fn try_alloc(&mut self) -> Option<Entity> {
+        if !self.has_room(1) {
+            return None;
+        }
+        let free = if self.recycle {
+            self.free.first()
+        } else {
+            None
+        };
+        match free {
+            Some(value) => {
                 self.free.remove(&value);
-                Entity::new(value.id)
+                Some(Entity::new(value.id))
             }
             None => {
+                if !self.next.is_valid() {
+                    return None;
+                }
                 let value = self.next;
                 self.next = Entity::new(self.next.id + 1);
-                value
+                Some(value)
             }
         }
     }
 
+    ///
+    /// Alloc `count` new `Entity`.
+    ///
+    /// Free ids are re-used first, then new ids are allocated as a single range.
+    ///
+    /// # Panics
+    ///
+    /// If all ids are allocated or the capacity limit is reached
+    pub fn alloc_batch(&mut self, count: usize) -> Vec<Entity> {
+        if !self.has_room(count) {
+            panic!("no more entity available");
+        }
+        let reused = if self.recycle { count } else { 0 };
+        let mut entities: Vec<Entity> = self.free.iter().take(reused).collect();
+        for entity in entities.iter() {
+            self.free.remove(entity);
+        }
+        let remaining = (count - entities.len()) as u64;
+        let start = self.next.id;
+        if start as u64 + remaining > Entity::INVALID.id as u64 {
+            panic!("no more entity available");
+        }
+        let end = start + remaining as u32;
+        entities.extend((start..end).map(Entity::new));
+        self.next = Entity::new(end);
+        entities
+    }
+
+    ///
+    /// Alloc the `Entity` with a specific id (e.g. to restore a saved world).
+    ///
+    /// Ids between the last allocated one and `id` are marked as free,
+    /// they are recorded as a single range (whatever the gap).
+    ///
+    /// # Example
+    /// ```rust
+    /// use entity_system::{AllocError, EntityAllocator, Entity};
+    ///
+    /// let mut allocator = EntityAllocator::new();
+    /// let entity = allocator.alloc_at(3).unwrap();
+    /// assert_eq!(entity, Entity::new(3));
+    /// assert_eq!(allocator.len(), 1);
+    /// assert_eq!(allocator.alloc_at(3), Err(AllocError::AlreadyAlive(entity)));
+    /// ```
+    pub fn alloc_at(&mut self, id: u32) -> Result<Entity, AllocError> {
+        let entity = Entity::new(id);
+        if !entity.is_valid() {
+            return Err(AllocError::InvalidId);
+        }
+        if self.is_alive(entity) || self.parked.contains(&entity) {
+            return Err(AllocError::AlreadyAlive(entity));
+        }
+        if !self.has_room(1) {
+            return Err(AllocError::LimitReached);
+        }
+        if id >= self.next.id {
+            self.free.insert_range(self.next.id, id);
+            self.next = Entity::new(id + 1);
+        } else {
+            self.free.remove(&entity);
+        }
+        Ok(entity)
+    }
+
     ///
     /// Free an `Entity`. `Entity` id could be re-used
+    ///
+    /// Invalid, never allocated or already freed `Entity` are ignored.
     pub fn free(&mut self, entity: Entity) {
-        self.free.insert(entity);
+        if self.parked.remove(&entity) || self.is_alive(entity) {
+            self.free.insert(entity);
+            self.bump_version(entity);
+        }
+    }
+
+    ///
+    /// Set aside an allocated `Entity`: it is no longer alive, but its id is re-used
+    /// only by [`EntityAllocator::unpark`].
+    pub fn park(&mut self, entity: Entity) {
+        if self.is_alive(entity) && self.parked.insert(entity) {
+            self.bump_version(entity);
+        }
+    }
+
+    ///
+    /// Get back a parked `Entity`, `None` if there is none.
+    pub fn unpark(&mut self) -> Option<Entity> {
+        let entity = self.parked.iter().next().copied()?;
+        self.parked.remove(&entity);
+        Some(entity)
+    }
+
+    ///
+    /// Get the number of parked `Entity`
+    pub fn parked_len(&self) -> usize {
+        self.parked.len()
+    }
+
+    fn bump_version(&mut self, entity: Entity) {
+        // sparse: ids could be far apart (cf alloc_at)
+        let version = self.versions.entry(entity).or_default();
+        *version = version.wrapping_add(1);
+    }
+
+    ///
+    /// Release the highest ids if they are free, so they are handed out again by `alloc`.
+    ///
+    /// It should not be called if freed `Entity` with high ids are still referenced
+    /// ([`WeakEntity`] stay safe: versions are kept).
+    ///
+    /// # Example
+    /// ```rust
+    /// use entity_system::EntityAllocator;
+    ///
+    /// let mut allocator = EntityAllocator::new();
+    /// let entities = allocator.alloc_batch(4);
+    /// allocator.free(entities[1]);
+    /// allocator.free(entities[3]);
+    ///
+    /// allocator.compact();
+    /// assert!(!allocator.is_free(entities[3]));
+    /// assert!(allocator.is_free(entities[1]));
+    /// assert_eq!(allocator.len(), 2);
+    /// ```
+    pub fn compact(&mut self) {
+        while self.next.id > 0 {
+            if let Some(start) = self.free.remove_range_to(self.next.id) {
+                self.next = Entity::new(start);
+                continue;
+            }
+            let last = Entity::new(self.next.id - 1);
+            if !self.free.remove(&last) {
+                break;
+            }
+            self.next = last;
+        }
+        self.free.shrink_to_fit();
+    }
+
+    ///
+    /// Check if an `Entity` is allocated
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        entity.id < self.next.id && !self.free.contains(&entity) && !self.parked.contains(&entity)
+    }
+
+    ///
+    /// Create a [`WeakEntity`] on an `Entity`.
+    ///
+    /// If the `Entity` is not allocated, the `WeakEntity` is never upgraded.
+    pub fn weak(&self, entity: Entity) -> WeakEntity {
+        if self.is_alive(entity) {
+            WeakEntity {
+                entity,
+                version: self.version(entity),
+            }
+        } else {
+            WeakEntity {
+                entity: Entity::INVALID,
+                version: 0,
+            }
+        }
+    }
+
+    ///
+    /// Get the `Entity` of a [`WeakEntity`], `None` if it has been freed since.
+    pub fn upgrade(&self, weak: WeakEntity) -> Option<Entity> {
+        if self.is_alive(weak.entity) && self.version(weak.entity) == weak.version {
+            Some(weak.entity)
+        } else {
+            None
+        }
+    }
+
+    fn version(&self, entity: Entity) -> u32 {
+        self.versions.get(&entity).copied().unwrap_or_default()
+    }
+
+    ///
+    /// Iter over freed `Entity`, waiting to be re-used (in no particular order)
+    pub fn free_ids(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.free.iter()
     }
 
     ///
-    /// Iter over allocated `Entity`
+    /// Check if an `Entity` has been freed and not yet re-used
+    pub fn is_free(&self, entity: Entity) -> bool {
+        self.free.contains(&entity)
+    }
+
+    ///
+    /// Iter over allocated `Entity`, in ascending id order
     pub fn iter(&self) -> EntityAllocatorIterator {
         EntityAllocatorIterator::new(self)
     }
+
+    fn has_room(&self, count: usize) -> bool {
+        // parked entities could be re-activated at any time by unpark
+        match self.limit {
+            Some(limit) => self.len() + self.parked.len() + count <= limit as usize,
+            None => true,
+        }
+    }
 }
 
 impl Default for EntityAllocator {
@@ -120,6 +464,8 @@ impl Default for EntityAllocator {
 ///
 /// Iterator over [`EntityAllocator`].
 ///
+/// Allocated entities are yielded in ascending id order.
+///
 /// Cf [`EntityAllocator`] to have an example
 pub struct EntityAllocatorIterator<'a> {
     allocator: &'a EntityAllocator,
@@ -139,8 +485,16 @@ impl<'a> EntityAllocatorIterator<'a> {
     }
 
     fn next_free_entity(&mut self) {
-        while self.allocator.free.contains(&self.current) {
-            self.current = Entity::new(self.current.id + 1);
+        while self.allocator.free.contains(&self.current)
+            || self.allocator.parked.contains(&self.current)
+        {
+            // skip a whole range of free ids at once
+            let next = self
+                .allocator
+                .free
+                .range_end(self.current)
+                .unwrap_or(self.current.id + 1);
+            self.current = Entity::new(next);
             if self.current == self.allocator.next {
                 break;
             }
@@ -162,3 +516,96 @@ impl<'a> Iterator for EntityAllocatorIterator<'a> {
         }
     }
 }
+
+///
+/// Free ids of [`EntityAllocator`]: freed ids, and ranges of ids skipped by
+/// [`EntityAllocator::alloc_at`] (kept as ranges to not allocate one item per id).
+#[derive(Default)]
+struct FreeIds {
+    ids: HashSet<Entity>,
+    ranges: BTreeMap<u32, u32>,
+    ranges_len: usize,
+}
+
+impl FreeIds {
+    fn len(&self) -> usize {
+        self.ids.len() + self.ranges_len
+    }
+
+    fn contains(&self, entity: &Entity) -> bool {
+        self.ids.contains(entity) || self.range_end(*entity).is_some()
+    }
+
+    fn first(&self) -> Option<Entity> {
+        self.ids
+            .iter()
+            .next()
+            .copied()
+            .or_else(|| self.ranges.keys().next().copied().map(Entity::new))
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.ids.iter().copied().chain(
+            self.ranges
+                .iter()
+                .flat_map(|(start, end)| (*start..*end).map(Entity::new)),
+        )
+    }
+
+    fn insert(&mut self, entity: Entity) -> bool {
+        self.ids.insert(entity)
+    }
+
+    fn insert_range(&mut self, start: u32, end: u32) {
+        if start < end {
+            self.ranges.insert(start, end);
+            self.ranges_len += (end - start) as usize;
+        }
+    }
+
+    fn remove(&mut self, entity: &Entity) -> bool {
+        if self.ids.remove(entity) {
+            return true;
+        }
+        let (start, end) = match self.range(*entity) {
+            Some(range) => range,
+            None => return false,
+        };
+        self.ranges.remove(&start);
+        if start < entity.id {
+            self.ranges.insert(start, entity.id);
+        }
+        if entity.id + 1 < end {
+            self.ranges.insert(entity.id + 1, end);
+        }
+        self.ranges_len -= 1;
+        true
+    }
+
+    /// Remove the range ending at `end`, return its start.
+    fn remove_range_to(&mut self, end: u32) -> Option<u32> {
+        let (start, _) = self
+            .range(Entity::new(end.checked_sub(1)?))
+            .filter(|(_, range_end)| *range_end == end)?;
+        self.ranges.remove(&start);
+        self.ranges_len -= (end - start) as usize;
+        Some(start)
+    }
+
+    /// Get the end of the range containing `entity`.
+    fn range_end(&self, entity: Entity) -> Option<u32> {
+        self.range(entity).map(|(_, end)| end)
+    }
+
+    fn range(&self, entity: Entity) -> Option<(u32, u32)> {
+        self.ranges
+            .range(..=entity.id)
+            .next_back()
+            .filter(|(_, end)| entity.id < **end)
+            .map(|(start, end)| (*start, *end))
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.ids.shrink_to_fit();
+    }
+}
@@ -1,13 +1,27 @@
-use crate::entity::{Entity, EntityAllocator, EntityAllocatorIterator};
-use crate::storage::Storage;
-use std::cell::{Ref, RefMut};
+use crate::archetype::ArchetypeIndex;
+use crate::command_buffer::CommandBuffer;
+use crate::entity::{Entity, EntityAllocator, EntityAllocatorIterator, WeakEntity};
+use crate::event_dispatcher::{AccessEventAdapter, EventDispatcher};
+#[cfg(feature = "rayon")]
+use crate::storage::ParStorage;
+use crate::storage::{Storage, StorageError, TrackedStorage};
+use crate::world_diff::{ComponentDiff, WorldDiff};
+use std::any::{Any, TypeId};
+use std::cell::{Cell, Ref, RefCell, RefMut};
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::rc::Rc;
 
 ///
 /// Create EntityManagerComponent
 ///
 /// # Arguments
 /// * `name`  name of EntityManagerComponent class
-/// * `component` list of component
+/// * `component` list of component, generic instantiations (e.g. `Gauge<Health>`) are allowed.
+///   The storage is [`Component::Storage`], it could be overridden by `component => storage`
+///   (storage type without generic parameter, as [`impl_component`]).
 ///
 /// # Examples
 /// ```rust
@@ -35,51 +49,238 @@ use std::cell::{Ref, RefMut};
 /// create_entity_manager_component!(EMC { Position, Velocity});
 /// type EntityManager = entity_system::EntityManager<EMC>;
 /// type Query = entity_system::Query<EMC>;
+///
+/// // Velocity stored in a TrackedVecStorage<Velocity>
+/// create_entity_manager_component!(TrackedEMC { Position, Velocity => entity_system::TrackedVecStorage });
 ///```
 #[macro_export]
 macro_rules! create_entity_manager_component {
-    ($name:ident { $($component:ident),* }) => {
+    ($name:ident { $($component:ident $(<$($generic:ident),+>)? $(=> $($storage:ident)::+)?),* }) => {
         paste::paste! {
-            pub struct $name {
+            entity_system::create_entity_manager_component!(@impl $name {
+                $(
+                [<cpt $component:snake $($($generic:snake)+)?>]:
+                    ($component $(<$($generic),+>)?):
+                    (entity_system::create_entity_manager_component!(
+                        @storage ($component $(<$($generic),+>)?) $(=> $($storage)::+)?
+                    )):
+                    (concat!(stringify!($component) $(, "<", stringify!($($generic),+), ">")?))
+                ),*
+            });
+        }
+    };
+    (@storage ($component:ty)) => {
+        <$component as entity_system::Component>::Storage
+    };
+    (@storage ($component:ty) => $($storage:ident)::+) => {
+        $($storage)::+<$component>
+    };
+    (@impl $name:ident { $($field:ident: ($component:ty): ($storage:ty): ($label:expr)),* }) => {
+        pub struct $name {
+            $(
+            $field: std::cell::RefCell<$storage>,
+            )*
+        }
+
+        impl entity_system::EntityManagerComponent for $name {
+            fn free(&mut self, entity: entity_system::Entity) {
+                use entity_system::Storage;
                 $(
-                [<cpt $component:snake>]: std::cell::RefCell<<$component as entity_system::Component>::Storage>,
+                self.$field.borrow_mut().free(entity);
                 )*
             }
 
-            impl entity_system::EntityManagerComponent for $name {
-                fn free(&mut self, entity: entity_system::Entity) {
-                    use entity_system::Storage;
-                    $(
-                    self.[<cpt $component:snake>].borrow_mut().free(entity);
-                    )*
-                }
+            fn set_tick(&mut self, tick: u64) {
+                use entity_system::Storage;
+                $(
+                self.$field.borrow_mut().set_tick(tick);
+                )*
+            }
+
+            fn reset_access_stats(&mut self) {
+                use entity_system::Storage;
+                $(
+                self.$field.borrow_mut().reset_access_stats();
+                )*
+            }
+
+            fn shrink_to_fit(&mut self) {
+                use entity_system::Storage;
+                $(
+                self.$field.borrow_mut().shrink_to_fit();
+                )*
             }
 
-            impl Default for $name {
-                fn default() -> Self {
-                    Self {
-                        $(
-                        [<cpt $component:snake>]: std::cell::RefCell::new(Default::default()),
-                        )*
+            fn reset(&mut self, entity: entity_system::Entity) {
+                use entity_system::Storage;
+                $(
+                {
+                    let storage = self.$field.get_mut();
+                    if storage.has(entity) {
+                        storage.free(entity);
+                        storage.alloc(entity);
                     }
                 }
+                )*
             }
 
-            $(
-            impl entity_system::StorageAccess<$component> for $name
-            where
-                $component : entity_system::Component,
-                <$component as entity_system::Component>::Storage : entity_system::Storage<$component>,
-            {
-                fn get(&self) -> std::cell::Ref<<$component as entity_system::Component>::Storage> {
-                    self.[<cpt $component:snake>].borrow()
+            fn schema() -> &'static [&'static str] {
+                const SCHEMA: &[&str] = &[$($label),*];
+                SCHEMA
+            }
+
+            fn component_count(&self, entity: entity_system::Entity) -> usize {
+                use entity_system::Storage;
+                0 $(+ self.$field.borrow().has(entity) as usize)*
+            }
+
+            fn stats(
+                &self,
+                alive: Option<&dyn Fn(entity_system::Entity) -> bool>,
+            ) -> Vec<(&'static str, Option<usize>)> {
+                use entity_system::Storage;
+                vec![
+                $(
+                ($label, self.$field.try_borrow().ok().map(|storage| match alive {
+                    Some(alive) => {
+                        let mut count = 0;
+                        storage.for_each(&mut |entity, _| count += alive(entity) as usize);
+                        count
+                    }
+                    None => storage.len(),
+                })),
+                )*
+                ]
+            }
+        }
+
+        // `for<'a>` defers the bounds check: the impl only exists if all components are `Clone`
+        impl entity_system::CloneEntityManagerComponent for $name
+        where
+            $(for<'a> $component: Clone,)*
+        {
+            fn clone_from_mapped(
+                &mut self,
+                other: &Self,
+                mapping: &std::collections::HashMap<entity_system::Entity, entity_system::Entity>,
+            ) {
+                use entity_system::Storage;
+                $(
+                {
+                    let dst = self.$field.get_mut();
+                    other.$field.borrow().for_each(&mut |entity, value| {
+                        if let Some(new_entity) = mapping.get(&entity) {
+                            dst.alloc(*new_entity);
+                            *dst.get_mut(*new_entity) = value.clone();
+                        }
+                    });
                 }
+                )*
+            }
+        }
 
-                fn get_mut(&self) -> std::cell::RefMut<<$component as entity_system::Component>::Storage> {
-                    self.[<cpt $component:snake>].borrow_mut()
+        impl Default for $name {
+            fn default() -> Self {
+                Self {
+                    $(
+                    $field: std::cell::RefCell::new(Default::default()),
+                    )*
                 }
             }
-            )*
+        }
+
+        $(
+        impl entity_system::StorageAccess<$component> for $name
+        where
+            $component : entity_system::Component,
+        {
+            type Storage = $storage;
+
+            fn get(&self) -> std::cell::Ref<$storage> {
+                self.$field.try_borrow().unwrap_or_else(|_| {
+                    panic!("{} storage already mutably borrowed", $label)
+                })
+            }
+
+            fn get_mut(&self) -> std::cell::RefMut<$storage> {
+                self.$field.try_borrow_mut().unwrap_or_else(|_| {
+                    panic!("{} storage already borrowed", $label)
+                })
+            }
+        }
+        )*
+    };
+}
+
+///
+/// Implement [`Component`] for a type.
+///
+/// The storage is [`crate::BasicVecStorage`] by default, it could be overridden.
+///
+/// # Arguments
+/// * `generics` (optional) generic parameters, as `<Kind>`, to implement it for all instantiations
+///   (bounds go in a trailing `where` clause)
+/// * `component` component type, possibly a generic instantiation (e.g. `Gauge<Health>`)
+/// * `storage` (optional) storage type, without generic parameter
+///
+/// # Examples
+/// ```rust
+/// use entity_system::impl_component;
+///
+/// #[derive(Default)]
+/// pub struct Position {
+///     pub x: f32,
+///     pub y: f32,
+/// }
+///
+/// #[derive(Default)]
+/// pub struct Velocity {
+///     pub x: f32,
+///     pub y: f32,
+/// }
+///
+/// #[derive(Default)]
+/// pub struct Gauge<Kind> {
+///     pub value: u32,
+///     pub kind: std::marker::PhantomData<Kind>,
+/// }
+///
+/// #[derive(Default)]
+/// pub struct Level<Kind> {
+///     pub value: u32,
+///     pub kind: std::marker::PhantomData<Kind>,
+/// }
+///
+/// // storage is BasicVecStorage<Position>
+/// impl_component!(Position);
+///
+/// // storage is TrackedVecStorage<Velocity>
+/// impl_component!(Velocity => entity_system::TrackedVecStorage);
+///
+/// // storage is BasicVecStorage<Gauge<Kind>>, for any Kind
+/// impl_component!(<Kind> Gauge<Kind> where Kind: Default);
+///
+/// // storage is TrackedVecStorage<Level<Position>>, only for Level<Position>
+/// impl_component!(Level<Position> => entity_system::TrackedVecStorage);
+/// ```
+#[macro_export]
+macro_rules! impl_component {
+    (<$($generic:ident),+> $component:ty $(where $($bound:tt)+)?) => {
+        $crate::impl_component!(
+            <$($generic),+> $component => $crate::BasicVecStorage $(where $($bound)+)?
+        );
+    };
+    (<$($generic:ident),+> $component:ty => $($storage:ident)::+ $(where $($bound:tt)+)?) => {
+        impl<$($generic),+> $crate::Component for $component $(where $($bound)+)? {
+            type Storage = $($storage)::+<$component>;
+        }
+    };
+    ($component:ty) => {
+        $crate::impl_component!($component => $crate::BasicVecStorage);
+    };
+    ($component:ty => $($storage:ident)::+) => {
+        impl $crate::Component for $component {
+            type Storage = $($storage)::+<$component>;
         }
     };
 }
@@ -97,6 +298,8 @@ macro_rules! create_entity_manager_component {
 ///
 /// The most common ones are `BasicVecStorage`.
 ///
+/// Cf [`impl_component`] to implement it in one line.
+///
 /// ## Examples
 /// ```rust
 /// use entity_system::Component;
@@ -121,23 +324,27 @@ pub trait Component {
 pub trait StorageAccess<T>
 where
     T: Component,
-    T::Storage: Storage<T>,
 {
+    ///
+    /// Storage of the component, [`Component::Storage`] unless overridden
+    /// (cf [`create_entity_manager_component`]).
+    type Storage: Storage<T>;
+
     ///
     /// Return ref on Storage
     ///
     /// # Panics
     ///
-    /// If Storage could be borrow.
-    fn get(&self) -> Ref<T::Storage>;
+    /// If Storage is already mutably borrowed, the message contains the component name.
+    fn get(&self) -> Ref<Self::Storage>;
 
     ///
     /// Return ref mut on Storage
     ///
     /// # Panics
     ///
-    /// If Storage could be borrow mut.
-    fn get_mut(&self) -> RefMut<T::Storage>;
+    /// If Storage is already borrowed, the message contains the component name.
+    fn get_mut(&self) -> RefMut<Self::Storage>;
 }
 
 ///
@@ -146,6 +353,294 @@ pub trait EntityManagerComponent {
     ///
     /// Free all components for entity.
     fn free(&mut self, entity: Entity);
+
+    ///
+    /// Update the current tick of all storages.
+    ///
+    /// Only needed by storages with change tracking, does nothing by default.
+    fn set_tick(&mut self, _tick: u64) {}
+
+    ///
+    /// Reset access statistics of all storages.
+    ///
+    /// Only needed by storages with access statistics, does nothing by default.
+    fn reset_access_stats(&mut self) {}
+
+    ///
+    /// Get the number of allocated components, by component type name.
+    ///
+    /// Only components of entities accepted by `alive` are counted, if given.
+    /// The number is `None` if the storage is mutably borrowed.
+    ///
+    /// Empty by default.
+    fn stats(&self, _alive: Option<&dyn Fn(Entity) -> bool>) -> Vec<(&'static str, Option<usize>)> {
+        Vec::new()
+    }
+
+    ///
+    /// Get component type names, in declaration order.
+    ///
+    /// Empty by default: [`crate::SystemManager::validate`] reports all required components as missing.
+    fn schema() -> &'static [&'static str] {
+        &[]
+    }
+
+    ///
+    /// Get the number of components of entity.
+    fn component_count(&self, entity: Entity) -> usize;
+
+    ///
+    /// Reset all components of entity to their default value.
+    fn reset(&mut self, entity: Entity);
+
+    ///
+    /// Release unused memory of all storages.
+    ///
+    /// Does nothing by default.
+    fn shrink_to_fit(&mut self) {}
+}
+
+///
+/// Entity manager component whose components can be cloned into another one
+/// (cf [`EntityManager::merge_from`]).
+///
+/// Implemented by [`create_entity_manager_component`] if all components implement `Clone`.
+pub trait CloneEntityManagerComponent: EntityManagerComponent {
+    ///
+    /// Clone components of `other` entities into mapped entities.
+    fn clone_from_mapped(&mut self, other: &Self, mapping: &HashMap<Entity, Entity>);
+}
+
+///
+/// Optional component in a [`ComponentTuple`]: its ref is `None` if entity has not the component.
+///
+/// # Examples
+/// ```rust
+/// # use entity_system::{Component, BasicVecStorage, Opt, create_entity_manager_component};
+/// #
+/// # #[derive(Default)]
+/// # pub struct Position {
+/// #     pub x: f32,
+/// #     pub y: f32,
+/// # }
+/// #
+/// # impl Component for Position {
+/// #     type Storage = BasicVecStorage<Self>;
+/// # }
+/// #
+/// # #[derive(Default)]
+/// # pub struct Velocity {
+/// #     pub x: f32,
+/// #     pub y: f32,
+/// # }
+/// #
+/// # impl Component for Velocity {
+/// #     type Storage = BasicVecStorage<Self>;
+/// # }
+/// #
+/// # create_entity_manager_component!(EMC { Position, Velocity });
+/// # type EntityManager = entity_system::EntityManager<EMC>;
+/// #
+/// let mut entity_manager = EntityManager::new();
+/// let entity = entity_manager.create_entity();
+/// entity_manager.add_component::<Position>(entity);
+///
+/// for (entity, (position, velocity)) in entity_manager.iter_with::<(Position, Opt<Velocity>)>() {
+///     match velocity {
+///         Some(velocity) => println!("{} moves to {}", entity.id, position.x + velocity.x),
+///         None => println!("{} stays at {}", entity.id, position.x),
+///     }
+/// }
+/// ```
+pub struct Opt<T>(PhantomData<T>);
+
+///
+/// Item of a [`ComponentTuple`]: a component, or an optional component ([`Opt`]).
+pub trait ComponentItem<'a, EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default,
+{
+    ///
+    /// Ref on component
+    type Ref;
+
+    ///
+    /// Get ref of component from an entity.
+    ///
+    /// # Panics
+    ///
+    /// if entity has not the (required) component
+    fn get(
+        entity_manager: &'a EntityManager<EntityManagerComponentType>,
+        entity: Entity,
+    ) -> Self::Ref;
+
+    ///
+    /// Check if an entity has the component, always `true` for an optional component.
+    fn has(entity_manager: &EntityManager<EntityManagerComponentType>, entity: Entity) -> bool;
+
+    ///
+    /// Get type id of component, `None` for an optional component.
+    fn type_id() -> Option<TypeId>;
+}
+
+impl<'a, EntityManagerComponentType, T> ComponentItem<'a, EntityManagerComponentType> for T
+where
+    EntityManagerComponentType: EntityManagerComponent + Default + StorageAccess<T>,
+    T: Component + 'static,
+{
+    type Ref = Ref<'a, T>;
+
+    fn get(
+        entity_manager: &'a EntityManager<EntityManagerComponentType>,
+        entity: Entity,
+    ) -> Self::Ref {
+        entity_manager.get_component::<T>(entity)
+    }
+
+    fn has(entity_manager: &EntityManager<EntityManagerComponentType>, entity: Entity) -> bool {
+        entity_manager.has_component::<T>(entity)
+    }
+
+    fn type_id() -> Option<TypeId> {
+        Some(TypeId::of::<T>())
+    }
+}
+
+impl<'a, EntityManagerComponentType, T> ComponentItem<'a, EntityManagerComponentType> for Opt<T>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default + StorageAccess<T>,
+    T: Component + 'static,
+{
+    type Ref = Option<Ref<'a, T>>;
+
+    fn get(
+        entity_manager: &'a EntityManager<EntityManagerComponentType>,
+        entity: Entity,
+    ) -> Self::Ref {
+        if entity_manager.has_component::<T>(entity) {
+            Some(entity_manager.get_component::<T>(entity))
+        } else {
+            None
+        }
+    }
+
+    fn has(_entity_manager: &EntityManager<EntityManagerComponentType>, _entity: Entity) -> bool {
+        true
+    }
+
+    fn type_id() -> Option<TypeId> {
+        None
+    }
+}
+
+///
+/// Tuple of components, cf [`EntityManager::components_of`].
+///
+/// Implemented for tuples up to 6 components, each one may be optional (cf [`Opt`]).
+pub trait ComponentTuple<'a, EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default,
+{
+    ///
+    /// Tuple of refs on components
+    type Refs;
+
+    ///
+    /// Get refs of components from an entity.
+    ///
+    /// # Panics
+    ///
+    /// if entity has not one of the components
+    fn get(
+        entity_manager: &'a EntityManager<EntityManagerComponentType>,
+        entity: Entity,
+    ) -> Self::Refs;
+
+    ///
+    /// Get refs of components from an entity, `None` if entity has not one of the components.
+    fn try_get(
+        entity_manager: &'a EntityManager<EntityManagerComponentType>,
+        entity: Entity,
+    ) -> Option<Self::Refs>;
+
+    ///
+    /// Check if an entity has all components.
+    fn has(entity_manager: &EntityManager<EntityManagerComponentType>, entity: Entity) -> bool;
+
+    ///
+    /// Get type ids of (required) components.
+    fn type_ids() -> Vec<TypeId>;
+}
+
+macro_rules! impl_component_tuple {
+    ($($component:ident),*) => {
+        impl<'a, EntityManagerComponentType, $($component),*> ComponentTuple<'a, EntityManagerComponentType> for ($($component,)*)
+        where
+            EntityManagerComponentType: EntityManagerComponent + Default,
+            $(
+            $component: ComponentItem<'a, EntityManagerComponentType>,
+            )*
+        {
+            type Refs = ($($component::Ref,)*);
+
+            fn get(entity_manager: &'a EntityManager<EntityManagerComponentType>, entity: Entity) -> Self::Refs {
+                ($($component::get(entity_manager, entity),)*)
+            }
+
+            fn try_get(entity_manager: &'a EntityManager<EntityManagerComponentType>, entity: Entity) -> Option<Self::Refs> {
+                if Self::has(entity_manager, entity) {
+                    Some(Self::get(entity_manager, entity))
+                } else {
+                    None
+                }
+            }
+
+            fn has(entity_manager: &EntityManager<EntityManagerComponentType>, entity: Entity) -> bool {
+                $($component::has(entity_manager, entity))&&*
+            }
+
+            fn type_ids() -> Vec<TypeId> {
+                vec![$($component::type_id()),*].into_iter().flatten().collect()
+            }
+        }
+    };
+}
+
+impl_component_tuple!(A);
+impl_component_tuple!(A, B);
+impl_component_tuple!(A, B, C);
+impl_component_tuple!(A, B, C, D);
+impl_component_tuple!(A, B, C, D, E);
+impl_component_tuple!(A, B, C, D, E, F);
+
+///
+/// Event pushed when an entity is created, cf [`EntityManager::set_dispatcher`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct EntityCreated(pub Entity);
+
+///
+/// Event pushed when an entity is deleted, cf [`EntityManager::set_dispatcher`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct EntityDeleted(pub Entity);
+
+type EntityHook = Box<dyn FnMut(Entity)>;
+
+type Prototype<T> = Box<dyn Fn(&mut T)>;
+
+type ChangeHook<EntityManagerComponentType> =
+    Rc<dyn Fn(&EntityManager<EntityManagerComponentType>, Entity)>;
+
+///
+/// Summary of [`EntityManager`] state, cf [`EntityManager::stats`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EntityManagerStats {
+    ///
+    /// Number of alive entities.
+    pub entities: usize,
+    ///
+    /// Number of allocated components, by component type name.
+    pub components: Vec<(&'static str, usize)>,
 }
 
 ///
@@ -175,6 +670,15 @@ where
 {
     components: EntityManagerComponentType,
     allocator: EntityAllocator,
+    generation: Cell<u64>,
+    tick: u64,
+    archetypes: RefCell<ArchetypeIndex>,
+    on_create: Option<EntityHook>,
+    on_delete: Option<EntityHook>,
+    prototypes: HashMap<TypeId, Box<dyn Any>>,
+    pool: HashMap<Entity, Vec<TypeId>>,
+    pool_capacity: Option<usize>,
+    on_change: HashMap<TypeId, Vec<ChangeHook<EntityManagerComponentType>>>,
 }
 
 impl<EntityManagerComponentType> EntityManager<EntityManagerComponentType>
@@ -206,6 +710,26 @@ where
         Self {
             components: Default::default(),
             allocator: Default::default(),
+            generation: Cell::new(0),
+            tick: 0,
+            archetypes: Default::default(),
+            on_create: None,
+            on_delete: None,
+            prototypes: HashMap::new(),
+            pool: HashMap::new(),
+            pool_capacity: None,
+            on_change: HashMap::new(),
+        }
+    }
+
+    ///
+    /// Create new instance with at most `limit` live entities.
+    ///
+    /// Cf [`EntityManager::try_create_entity`].
+    pub fn with_capacity_limit(limit: u32) -> Self {
+        Self {
+            allocator: EntityAllocator::with_capacity_limit(limit),
+            ..Self::new()
         }
     }
 
@@ -233,11 +757,14 @@ where
     /// let entity = entity_manager.create_entity();
     /// ```
     pub fn create_entity(&mut self) -> Entity {
-        self.allocator.alloc()
+        self.touch();
+        let entity = self.allocator.alloc();
+        self.created(entity);
+        entity
     }
 
     ///
-    /// Delete an entity.
+    /// Create new entity, `None` if the capacity limit is reached.
     ///
     /// # Examples
     /// ```rust
@@ -256,22 +783,19 @@ where
     /// # create_entity_manager_component!(EMC { Position });
     /// # type EntityManager = entity_system::EntityManager<EMC>;
     /// #
-    /// let mut entity_manager = EntityManager::new();
-    /// let entity = entity_manager.create_entity();
-    ///
-    /// //
-    /// // lot of things
-    /// //
-    ///
-    /// entity_manager.delete_entity(entity);
+    /// let mut entity_manager = EntityManager::with_capacity_limit(1);
+    /// assert!(entity_manager.try_create_entity().is_some());
+    /// assert!(entity_manager.try_create_entity().is_none());
     /// ```
-    pub fn delete_entity(&mut self, entity: Entity) {
-        self.allocator.free(entity);
-        self.components.free(entity);
+    pub fn try_create_entity(&mut self) -> Option<Entity> {
+        let entity = self.allocator.try_alloc()?;
+        self.touch();
+        self.created(entity);
+        Some(entity)
     }
 
     ///
-    /// Add component to an entity. The component is initialized with default value.
+    /// Create `count` new entities.
     ///
     /// # Examples
     /// ```rust
@@ -291,21 +815,23 @@ where
     /// # type EntityManager = entity_system::EntityManager<EMC>;
     /// #
     /// let mut entity_manager = EntityManager::new();
-    /// let entity = entity_manager.create_entity();
-    ///
-    /// entity_manager.add_component::<Position>(entity);
+    /// let entities = entity_manager.create_entities(10);
+    /// assert_eq!(entities.len(), 10);
     /// ```
-    pub fn add_component<T>(&self, entity: Entity)
-    where
-        EntityManagerComponentType: StorageAccess<T>,
-        T: Component,
-        T::Storage: Storage<T>,
-    {
-        self.get_storage_mut().alloc(entity);
+    pub fn create_entities(&mut self, count: usize) -> Vec<Entity> {
+        self.touch();
+        let entities = self.allocator.alloc_batch(count);
+        for entity in entities.iter() {
+            self.created(*entity);
+        }
+        entities
     }
 
     ///
-    /// Add component to an entity and initialize with closure f.
+    /// Delete an entity.
+    ///
+    /// Nothing is done if the entity is not alive (already deleted or never created),
+    /// except for a pooled entity (cf [`EntityManager::despawn_pooled`]) which is deleted.
     ///
     /// # Examples
     /// ```rust
@@ -327,63 +853,1923 @@ where
     /// let mut entity_manager = EntityManager::new();
     /// let entity = entity_manager.create_entity();
     ///
-    /// entity_manager.add_component_with::<Position, _>(entity, |position|{
-    ///   position.x = 20.0;
-    ///   position.y = 50.0;
+    /// //
+    /// // lot of things
+    /// //
+    ///
+    /// entity_manager.delete_entity(entity);
+    /// ```
+    pub fn delete_entity(&mut self, entity: Entity) {
+        let pooled = self.pool.remove(&entity).is_some();
+        if !pooled && !self.is_alive(entity) {
+            return;
+        }
+        self.touch();
+        self.archetypes.get_mut().remove_entity(entity);
+        self.allocator.free(entity);
+        self.components.free(entity);
+        // already notified by despawn_pooled
+        if pooled {
+            return;
+        }
+        if let Some(on_delete) = self.on_delete.as_mut() {
+            on_delete(entity);
+        }
+    }
+
+    ///
+    /// Create an entity, re-using a pooled entity (cf [`EntityManager::despawn_pooled`]) if any.
+    ///
+    /// A re-used entity keeps the components and values it had when despawned,
+    /// cf [`EntityManager::recycle_pooled`] to reset them.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let bullet = entity_manager.spawn_pooled();
+    /// entity_manager.add_component_with::<Position, _>(bullet, |position| position.x = 5.0);
+    ///
+    /// entity_manager.despawn_pooled(bullet);
+    /// assert!(!entity_manager.is_alive(bullet));
+    ///
+    /// let bullet = entity_manager.spawn_pooled();
+    /// assert_eq!(entity_manager.get_component::<Position>(bullet).x, 5.0);
+    /// ```
+    pub fn spawn_pooled(&mut self) -> Entity {
+        match self.allocator.unpark() {
+            Some(entity) => {
+                self.touch();
+                let types = self.pool.remove(&entity).unwrap_or_default();
+                self.archetypes.get_mut().restore_entity(entity, types);
+                self.created(entity);
+                entity
+            }
+            None => self.create_entity(),
+        }
+    }
+
+    ///
+    /// Delete an entity, keeping its components to be re-used by [`EntityManager::spawn_pooled`].
+    ///
+    /// If the pool is full (cf [`EntityManager::set_pool_capacity`]), the entity is deleted.
+    pub fn despawn_pooled(&mut self, entity: Entity) {
+        if !self.is_alive(entity) {
+            return;
+        }
+        if self
+            .pool_capacity
+            .is_some_and(|capacity| self.pool.len() >= capacity)
+        {
+            self.delete_entity(entity);
+            return;
+        }
+        self.touch();
+        let types = self.archetypes.get_mut().take_entity(entity);
+        self.pool.insert(entity, types);
+        self.allocator.park(entity);
+        if let Some(on_delete) = self.on_delete.as_mut() {
+            on_delete(entity);
+        }
+    }
+
+    ///
+    /// Reset all components of an entity to their default value (e.g. after `spawn_pooled`).
+    pub fn recycle_pooled(&mut self, entity: Entity) {
+        self.components.reset(entity);
+    }
+
+    ///
+    /// Set the max number of pooled entities (unbounded by default).
+    ///
+    /// Pooled entities over the capacity are deleted.
+    pub fn set_pool_capacity(&mut self, capacity: usize) {
+        self.pool_capacity = Some(capacity);
+        while self.pool.len() > capacity {
+            match self.allocator.unpark() {
+                Some(entity) => {
+                    self.pool.remove(&entity);
+                    self.allocator.free(entity);
+                    self.components.free(entity);
+                }
+                None => break,
+            }
+        }
+    }
+
+    ///
+    /// Move all entities of `other` into this manager, with new ids.
+    ///
+    /// Components are cloned, they must implement `Clone` (cf [`CloneEntityManagerComponent`]).
+    ///
+    /// # Return
+    ///
+    /// The mapping from `other` entities to new entities, to fix up cross-references.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default, Clone)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut world = EntityManager::new();
+    /// world.create_entity();
+    ///
+    /// let mut prefab = EntityManager::new();
+    /// let entity = prefab.create_entity();
+    /// prefab.add_component_with::<Position, _>(entity, |position| position.x = 5.0);
+    ///
+    /// let mapping = world.merge_from(prefab);
+    /// assert_eq!(world.get_component::<Position>(mapping[&entity]).x, 5.0);
+    /// ```
+    pub fn merge_from(
+        &mut self,
+        mut other: EntityManager<EntityManagerComponentType>,
+    ) -> HashMap<Entity, Entity>
+    where
+        EntityManagerComponentType: CloneEntityManagerComponent,
+    {
+        let entities: Vec<Entity> = other.iter_all().collect();
+        let mut mapping = HashMap::with_capacity(entities.len());
+        for entity in entities {
+            let new_entity = self.create_entity();
+            let types = other.archetypes.get_mut().take_entity(entity);
+            self.archetypes.get_mut().restore_entity(new_entity, types);
+            mapping.insert(entity, new_entity);
+        }
+        self.components
+            .clone_from_mapped(&other.components, &mapping);
+        mapping
+    }
+
+    ///
+    /// Get differences from this manager (the source) to `other` (the target).
+    ///
+    /// Only components of the tuple C are compared, they must implement `PartialEq` and `Clone`.
+    /// Entities are identified by their id.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, ComponentChange, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default, Clone, PartialEq)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut before = EntityManager::new();
+    /// let entity = before.create_entity();
+    /// before.add_component::<Position>(entity);
+    ///
+    /// let mut after = EntityManager::new();
+    /// after.create_entity();
+    /// after.add_component_with::<Position, _>(entity, |position| position.x = 5.0);
+    ///
+    /// let diff = before.diff::<(Position,)>(&after);
+    /// assert_eq!(diff.components()[0].2, ComponentChange::Changed);
+    ///
+    /// before.apply_diff(diff);
+    /// assert_eq!(before.get_component::<Position>(entity).x, 5.0);
+    /// ```
+    pub fn diff<C>(
+        &self,
+        other: &EntityManager<EntityManagerComponentType>,
+    ) -> WorldDiff<EntityManagerComponentType>
+    where
+        EntityManagerComponentType: 'static,
+        C: ComponentDiff<EntityManagerComponentType>,
+    {
+        let created = other
+            .iter_all()
+            .filter(|entity| !self.is_alive(*entity))
+            .collect();
+        let destroyed = self
+            .iter_all()
+            .filter(|entity| !other.is_alive(*entity))
+            .collect();
+        let mut entities: Vec<Entity> = self.iter_all().chain(other.iter_all()).collect();
+        entities.sort_unstable();
+        entities.dedup();
+        let mut diff = WorldDiff::new(created, destroyed);
+        C::compare(&mut diff, self, other, &entities);
+        diff
+    }
+
+    ///
+    /// Apply differences computed by [`EntityManager::diff`]: entities are destroyed,
+    /// created (with the same ids) then components are added, updated and removed.
+    ///
+    /// Created entities whose id is already used are ignored.
+    pub fn apply_diff(&mut self, diff: WorldDiff<EntityManagerComponentType>)
+    where
+        EntityManagerComponentType: 'static,
+    {
+        for entity in diff.destroyed.iter().copied() {
+            if self.is_alive(entity) {
+                self.delete_entity(entity);
+            }
+        }
+        for entity in diff.created.iter().copied() {
+            if self.allocator.alloc_at(entity.id).is_ok() {
+                self.touch();
+                self.created(entity);
+            }
+        }
+        diff.apply_components(self);
+    }
+
+    ///
+    /// Apply commands recorded in `buffer`, in their recording order.
+    ///
+    /// cf [`CommandBuffer`].
+    pub fn apply(&mut self, buffer: CommandBuffer<EntityManagerComponentType>)
+    where
+        EntityManagerComponentType: 'static,
+    {
+        buffer.run(self);
+    }
+
+    ///
+    /// Release unused ids and memory, after a lot of entities have been deleted.
+    ///
+    /// The highest ids are released if free (cf [`EntityAllocator::compact`]) and
+    /// unused slots at the end of storages are released (cf [`Storage::shrink_to_fit`]).
+    ///
+    /// Released ids will be handed out again by `create_entity`, so it should not be called
+    /// if deleted `Entity` with high ids are still referenced (weak references stay safe).
+    pub fn compact(&mut self) {
+        self.allocator.compact();
+        self.components.shrink_to_fit();
+    }
+
+    ///
+    /// Get the number of alive entities, cf [`EntityAllocator::live_count`].
+    pub fn live_count(&self) -> usize {
+        self.allocator.live_count()
+    }
+
+    ///
+    /// Get the highest entity id ever handed out + 1, cf [`EntityAllocator::high_water`].
+    ///
+    /// A low `live_count() / high_water()` ratio means [`EntityManager::compact`] is worth calling.
+    pub fn high_water(&self) -> u32 {
+        self.allocator.high_water()
+    }
+
+    ///
+    /// Set the closure called after each entity creation.
+    pub fn set_on_create<F>(&mut self, f: F)
+    where
+        F: FnMut(Entity) + 'static,
+    {
+        self.on_create = Some(Box::new(f));
+    }
+
+    ///
+    /// Set the closure called after each entity deletion.
+    pub fn set_on_delete<F>(&mut self, f: F)
+    where
+        F: FnMut(Entity) + 'static,
+    {
+        self.on_delete = Some(Box::new(f));
+    }
+
+    ///
+    /// Push [`EntityCreated`] and [`EntityDeleted`] events to the dispatcher.
+    ///
+    /// It replaces closures set by [`EntityManager::set_on_create`] and
+    /// [`EntityManager::set_on_delete`]. The dispatcher is weakly referenced.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// use entity_system::{EntityCreated, EntityDeleted};
+    ///
+    /// entity_system::create_event_adapters!(EventAdapters { EntityCreated, EntityDeleted });
+    /// type EventDispatcher = entity_system::EventDispatcher<EventAdapters>;
+    ///
+    /// let event_dispatcher = EventDispatcher::new();
+    /// let mut entity_manager = EntityManager::new();
+    /// entity_manager.set_dispatcher(&event_dispatcher);
+    /// let entity = entity_manager.create_entity();
+    /// ```
+    pub fn set_dispatcher<EventAdapters, Context>(
+        &mut self,
+        dispatcher: &Rc<EventDispatcher<EventAdapters, Context>>,
+    ) where
+        EventAdapters: Default
+            + AccessEventAdapter<EntityCreated, Context>
+            + AccessEventAdapter<EntityDeleted, Context>
+            + 'static,
+        Context: 'static,
+    {
+        let weak = Rc::downgrade(dispatcher);
+        self.set_on_create(move |entity| {
+            if let Some(dispatcher) = weak.upgrade() {
+                dispatcher.push(EntityCreated(entity));
+            }
+        });
+        let weak = Rc::downgrade(dispatcher);
+        self.set_on_delete(move |entity| {
+            if let Some(dispatcher) = weak.upgrade() {
+                dispatcher.push(EntityDeleted(entity));
+            }
+        });
+    }
+
+    ///
+    /// Get the number of entities and components.
+    ///
+    /// # Panics
+    ///
+    /// If a storage is mutably borrowed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(entity);
+    /// entity_manager.create_entity();
+    ///
+    /// let stats = entity_manager.stats();
+    /// assert_eq!(stats.entities, 2);
+    /// assert_eq!(stats.components, [("Position", 1)]);
+    /// ```
+    pub fn stats(&self) -> EntityManagerStats {
+        EntityManagerStats {
+            entities: self.allocator.len(),
+            components: self
+                .component_stats()
+                .into_iter()
+                .map(|(name, count)| {
+                    let count = count
+                        .unwrap_or_else(|| panic!("{} storage already mutably borrowed", name));
+                    (name, count)
+                })
+                .collect(),
+        }
+    }
+
+    ///
+    /// Get names of all component types supported by the manager, in declaration order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Velocity {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Velocity {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position, Velocity });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// assert_eq!(EntityManager::schema(), ["Position", "Velocity"]);
+    /// ```
+    pub fn schema() -> &'static [&'static str] {
+        EntityManagerComponentType::schema()
+    }
+
+    ///
+    /// Get alive entities without any component, often a spawn bug.
+    ///
+    /// # Panics
+    ///
+    /// If a storage is mutably borrowed.
+    pub fn orphans(&self) -> Vec<Entity> {
+        self.iter_all()
+            .filter(|entity| self.components.component_count(*entity) == 0)
+            .collect()
+    }
+
+    ///
+    /// Reset access statistics of all storages, cf [`crate::BasicVecStorage::access_stats`].
+    ///
+    /// Only available with the `profiling` feature.
+    #[cfg(feature = "profiling")]
+    pub fn reset_access_stats(&mut self) {
+        self.components.reset_access_stats();
+    }
+
+    ///
+    /// Check if an entity is alive (created and not deleted).
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.allocator.is_alive(entity)
+    }
+
+    ///
+    /// Create a weak reference on an entity.
+    ///
+    /// Unlike [`Entity`], a [`WeakEntity`] is not upgraded once the entity is deleted,
+    /// even if its id is re-used by a new entity.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();
+    /// let weak = entity_manager.weak(entity);
+    /// assert_eq!(entity_manager.upgrade(weak), Some(entity));
+    ///
+    /// entity_manager.delete_entity(entity);
+    /// let entity = entity_manager.create_entity();
+    /// assert!(entity_manager.upgrade(weak).is_none());
+    /// ```
+    pub fn weak(&self, entity: Entity) -> WeakEntity {
+        self.allocator.weak(entity)
+    }
+
+    ///
+    /// Get the entity of a weak reference, `None` if the entity has been deleted.
+    pub fn upgrade(&self, weak: WeakEntity) -> Option<Entity> {
+        self.allocator.upgrade(weak)
+    }
+
+    ///
+    /// Delete all entities not matching the query.
+    ///
+    /// Entities are collected before being deleted.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component, Query};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let e1 = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(e1);
+    /// let e2 = entity_manager.create_entity();
+    ///
+    /// let mut query = Query::new();
+    /// query.check_component::<Position>();
+    /// entity_manager.retain(&query);
+    /// assert!(entity_manager.is_alive(e1));
+    /// assert!(!entity_manager.is_alive(e2));
+    /// ```
+    pub fn retain(&mut self, query: &Query<EntityManagerComponentType>) {
+        let entities: Vec<_> = self
+            .iter_all()
+            .filter(|entity| !query.check(self, *entity))
+            .collect();
+        for entity in entities {
+            self.delete_entity(entity);
+        }
+    }
+
+    ///
+    /// Set the value of T given by `add_component`, instead of the default value.
+    ///
+    /// It is also used by `add_component_with` (before calling the closure),
+    /// `try_add_component` and `get_or_add_component_*`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// #[derive(Default, Clone)]
+    /// pub struct Health {
+    ///     pub value: u32,
+    /// }
+    /// #
+    /// # impl Component for Health {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Health });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// entity_manager.set_prototype(Health { value: 100 });
+    ///
+    /// let entity = entity_manager.create_entity();
+    /// entity_manager.add_component::<Health>(entity);
+    /// assert_eq!(entity_manager.get_component::<Health>(entity).value, 100);
+    /// ```
+    pub fn set_prototype<T>(&mut self, value: T)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + Clone + 'static,
+    {
+        let init: Prototype<T> = Box::new(move |component| *component = value.clone());
+        self.prototypes.insert(TypeId::of::<T>(), Box::new(init));
+    }
+
+    ///
+    /// Add component to an entity. The component is initialized with default value
+    /// (or the prototype, cf [`EntityManager::set_prototype`]).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();
+    ///
+    /// entity_manager.add_component::<Position>(entity);
+    /// ```
+    pub fn add_component<T>(&self, entity: Entity)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+    {
+        let mut storage = self.storage_mut();
+        let added = !storage.has(entity);
+        self.on_component_added::<T>(entity);
+        storage.alloc(entity);
+        if added {
+            self.init_component::<T>(&mut storage, entity);
+        }
+    }
+
+    ///
+    /// Add component to an entity, without panic if memory allocation failed.
+    ///
+    /// The entity is left unchanged on error.
+    pub fn try_add_component<T>(&self, entity: Entity) -> Result<(), StorageError>
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+    {
+        let mut storage = self.storage_mut();
+        let added = !storage.has(entity);
+        storage.try_alloc(entity)?;
+        self.on_component_added::<T>(entity);
+        if added {
+            self.init_component::<T>(&mut storage, entity);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Add component to an entity and initialize with closure f.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();
+    ///
+    /// entity_manager.add_component_with::<Position, _>(entity, |position|{
+    ///   position.x = 20.0;
+    ///   position.y = 50.0;
+    /// });
+    /// ```
+    pub fn add_component_with<T, F>(&self, entity: Entity, f: F)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+        F: FnOnce(&mut T),
+    {
+        self.add_component::<T>(entity);
+        f(&mut *self.get_component_mut::<T>(entity));
+    }
+
+    ///
+    /// Add component with a value to several entities.
+    ///
+    /// The storage is borrowed only once, and reserved according to the iterator size hint.
+    /// An entity which already has the component gets the new value.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entities: Vec<_> = (0..10).map(|_| entity_manager.create_entity()).collect();
+    ///
+    /// entity_manager.add_components_from(entities.iter().enumerate().map(|(i, entity)| {
+    ///     (*entity, Position { x: i as f32, y: 0.0 })
+    /// }));
+    /// ```
+    pub fn add_components_from<T, I>(&self, items: I)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+        I: IntoIterator<Item = (Entity, T)>,
+    {
+        let items = items.into_iter();
+        let mut storage = self.storage_mut();
+        storage.reserve(items.size_hint().0);
+        for (entity, value) in items {
+            if !storage.has(entity) {
+                self.on_component_added::<T>(entity);
+                storage.alloc(entity);
+            }
+            *storage.get_mut(entity) = value;
+        }
+    }
+
+    ///
+    /// Get a mut ref of component from an entity, the component is added with default value if absent.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();
+    ///
+    /// let mut position = entity_manager.get_or_add_component_mut::<Position>(entity);
+    /// position.x = 5.0;
+    /// ```
+    pub fn get_or_add_component_mut<T>(&self, entity: Entity) -> RefMut<T>
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+    {
+        self.get_or_add_component_with(entity, |_| {})
+    }
+
+    ///
+    /// Get a mut ref of component from an entity, the component is added and initialized
+    /// with closure f if absent.
+    ///
+    /// f is called only if the component is added.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();
+    ///
+    /// let position = entity_manager.get_or_add_component_with::<Position, _>(entity, |position| {
+    ///     position.x = 20.0;
+    /// });
+    /// assert_eq!(position.x, 20.0);
+    /// drop(position);
+    ///
+    /// let position = entity_manager.get_or_add_component_with::<Position, _>(entity, |position| {
+    ///     position.x = 50.0;
+    /// });
+    /// assert_eq!(position.x, 20.0);
+    /// ```
+    pub fn get_or_add_component_with<T, F>(&self, entity: Entity, f: F) -> RefMut<T>
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+        F: FnOnce(&mut T),
+    {
+        let mut storage = self.storage_mut();
+        if !storage.has(entity) {
+            self.on_component_added::<T>(entity);
+            storage.alloc(entity);
+            self.init_component::<T>(&mut storage, entity);
+            f(storage.get_mut(entity));
+        }
+        RefMut::map(storage, |storage| storage.get_mut(entity))
+    }
+
+    ///
+    /// Remove component to an entity.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(entity);
+    ///
+    /// //
+    /// // lot of things
+    /// //
+    ///
+    /// entity_manager.remove_component::<Position>(entity);
+    /// ```
+    pub fn remove_component<T>(&self, entity: Entity)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+    {
+        self.on_component_removed::<T>(entity);
+        self.storage_mut().free(entity)
+    }
+
+    ///
+    /// Same as [`EntityManager::add_component`], with `&mut self`: no component or
+    /// iterator borrow could be alive, it is checked at compile time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();
+    /// entity_manager.add_component_mut::<Position>(entity);
+    ///
+    /// let position = entity_manager.get_component::<Position>(entity);
+    /// // does not compile, position is still borrowed
+    /// // entity_manager.remove_component_mut::<Position>(entity);
+    /// drop(position);
+    /// entity_manager.remove_component_mut::<Position>(entity);
+    /// ```
+    pub fn add_component_mut<T>(&mut self, entity: Entity)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+    {
+        self.add_component::<T>(entity);
+    }
+
+    ///
+    /// Same as [`EntityManager::add_component_with`], with `&mut self`
+    /// (cf [`EntityManager::add_component_mut`]).
+    pub fn add_component_with_mut<T, F>(&mut self, entity: Entity, f: F)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+        F: FnOnce(&mut T),
+    {
+        self.add_component_with::<T, F>(entity, f);
+    }
+
+    ///
+    /// Same as [`EntityManager::remove_component`], with `&mut self`
+    /// (cf [`EntityManager::add_component_mut`]).
+    pub fn remove_component_mut<T>(&mut self, entity: Entity)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+    {
+        self.remove_component::<T>(entity);
+    }
+
+    ///
+    /// Remove a component from all entities.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component, Query};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// for entity in entity_manager.create_entities(3) {
+    ///     entity_manager.add_component::<Position>(entity);
+    /// }
+    ///
+    /// entity_manager.remove_component_all::<Position>();
+    /// assert_eq!(entity_manager.iter_archetype::<(Position,)>().count(), 0);
+    /// ```
+    pub fn remove_component_all<T>(&self)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+    {
+        let mut storage = self.storage_mut::<T>();
+        let mut entities = Vec::new();
+        storage.for_each(&mut |entity, _| entities.push(entity));
+        // pooled entities keep their components
+        let pooled = self.allocator.parked_len() > 0;
+        if pooled {
+            entities.retain(|entity| self.allocator.is_alive(*entity));
+        }
+        for entity in entities {
+            self.on_component_removed::<T>(entity);
+            if pooled {
+                storage.free(entity);
+            }
+        }
+        if !pooled {
+            storage.clear();
+        }
+    }
+
+    ///
+    /// Remove a component from all entities matching the query.
+    ///
+    /// Entities are collected before removing the component.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component, Query};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// for entity in entity_manager.create_entities(3) {
+    ///     entity_manager.add_component::<Position>(entity);
+    /// }
+    ///
+    /// let mut query = Query::new();
+    /// query.check_id_range(0..2);
+    /// entity_manager.remove_component_matching::<Position>(&query);
+    /// assert_eq!(entity_manager.iter_archetype::<(Position,)>().count(), 1);
+    /// ```
+    pub fn remove_component_matching<T>(&self, query: &Query<EntityManagerComponentType>)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+    {
+        let entities = self.collect(query);
+        let mut storage = self.storage_mut::<T>();
+        for entity in entities {
+            if storage.has(entity) {
+                self.on_component_removed::<T>(entity);
+                storage.free(entity);
+            }
+        }
+    }
+
+    ///
+    /// Move a component from an entity to another.
+    ///
+    /// The component is added to `to` if absent and removed from `from`.
+    ///
+    /// # Panics
+    ///
+    /// if `from` has not the component
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let e1 = entity_manager.create_entity();
+    /// let e2 = entity_manager.create_entity();
+    /// entity_manager.add_component_with::<Position, _>(e1, |position| position.x = 5.0);
+    ///
+    /// entity_manager.move_component::<Position>(e1, e2);
+    /// assert!(!entity_manager.has_component::<Position>(e1));
+    /// assert_eq!(entity_manager.get_component::<Position>(e2).x, 5.0);
+    /// ```
+    pub fn move_component<T>(&self, from: Entity, to: Entity)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + Default + 'static,
+    {
+        let mut storage = self.storage_mut();
+        let value = std::mem::take(storage.get_mut(from));
+        if from != to {
+            self.on_component_removed::<T>(from);
+            storage.free(from);
+        }
+        if !storage.has(to) {
+            self.on_component_added::<T>(to);
+            storage.alloc(to);
+        }
+        *storage.get_mut(to) = value;
+    }
+
+    ///
+    /// Swap component values of two entities.
+    ///
+    /// # Panics
+    ///
+    /// if one of the entities has not the component
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let e1 = entity_manager.create_entity();
+    /// let e2 = entity_manager.create_entity();
+    /// entity_manager.add_component_with::<Position, _>(e1, |position| position.x = 5.0);
+    /// entity_manager.add_component_with::<Position, _>(e2, |position| position.x = 6.0);
+    ///
+    /// entity_manager.swap_component::<Position>(e1, e2);
+    /// assert_eq!(entity_manager.get_component::<Position>(e1).x, 6.0);
+    /// assert_eq!(entity_manager.get_component::<Position>(e2).x, 5.0);
+    /// ```
+    pub fn swap_component<T>(&self, a: Entity, b: Entity)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component,
+    {
+        self.storage_mut().swap(a, b);
+    }
+
+    ///
+    /// Copy a component of an entity into another entity manager, for the same entity.
+    ///
+    /// The component is added to `dst` if absent. Entity ids are not checked against `dst`
+    /// allocator, managers are expected to share them.
+    ///
+    /// # Panics
+    ///
+    /// if entity has not the component
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default, Clone)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut server = EntityManager::new();
+    /// let mut client = EntityManager::new();
+    /// let entity = server.create_entity();
+    /// assert_eq!(client.create_entity(), entity);
+    /// server.add_component_with::<Position, _>(entity, |position| position.x = 5.0);
+    ///
+    /// server.copy_component_to::<Position>(&client, entity);
+    /// assert_eq!(client.get_component::<Position>(entity).x, 5.0);
+    /// ```
+    pub fn copy_component_to<T>(
+        &self,
+        dst: &EntityManager<EntityManagerComponentType>,
+        entity: Entity,
+    ) where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + Clone + 'static,
+    {
+        let value = self.get_component::<T>(entity).clone();
+        *dst.get_or_add_component_mut::<T>(entity) = value;
+    }
+
+    ///
+    /// Check if an entity has a component.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();
+    ///
+    /// assert!(!entity_manager.has_component::<Position>(entity));
+    /// entity_manager.add_component::<Position>(entity);
+    /// assert!(entity_manager.has_component::<Position>(entity));
+    ///
+    /// let entity = entity_system::Entity::INVALID;
+    /// assert!(!entity_manager.has_component::<Position>(entity));
+    /// ```
+    pub fn has_component<T>(&self, entity: Entity) -> bool
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component,
+    {
+        entity.is_valid() && self.storage().has(entity)
+    }
+
+    ///
+    /// Get a ref of component from an entity.
+    ///
+    /// # Panics
+    ///
+    /// if entity has not the component or entity has been deleted
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();    
+    /// entity_manager.add_component::<Position>(entity);
+    ///
+    /// let position = entity_manager.get_component::<Position>(entity);
+    /// println!("{}, {}", position.x, position.y);
+    /// ```
+    pub fn get_component<T>(&self, entity: Entity) -> Ref<T>
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component,
+    {
+        self.check_alive(entity);
+        Ref::map(self.storage(), |storage| storage.get(entity))
+    }
+
+    ///
+    /// Get a copy of component from an entity, the storage borrow is released immediately.
+    ///
+    /// # Panics
+    ///
+    /// if entity has not the component or entity has been deleted
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default, Clone)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(entity);
+    ///
+    /// let position = entity_manager.get_component_cloned::<Position>(entity);
+    /// entity_manager.remove_component::<Position>(entity);
+    /// println!("{}, {}", position.x, position.y);
+    /// ```
+    pub fn get_component_cloned<T>(&self, entity: Entity) -> T
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + Clone,
+    {
+        self.check_alive(entity);
+        self.storage::<T>().get(entity).clone()
+    }
+
+    ///
+    /// Get a copy of component from an entity, `None` if entity has not the component
+    /// or has been deleted.
+    ///
+    /// cf [`EntityManager::get_component_cloned`].
+    pub fn try_get_component_cloned<T>(&self, entity: Entity) -> Option<T>
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + Clone,
+    {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        self.storage::<T>().try_get(entity).cloned()
+    }
+
+    ///
+    /// Get a copy of component from an entity, `T::default()` if entity has not the component.
+    ///
+    /// Never panics, convenient when a missing component has a neutral value (e.g. a zero velocity).
+    pub fn get_component_or<T>(&self, entity: Entity) -> T
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + Copy + Default,
+    {
+        self.storage::<T>()
+            .try_get(entity)
+            .filter(|_| self.is_alive(entity))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    ///
+    /// Get a mut ref of component from an entity.
+    ///
+    /// # Panics
+    ///
+    /// if entity has not the component or entity has been deleted
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();    
+    /// entity_manager.add_component::<Position>(entity);
+    ///
+    /// let mut position = entity_manager.get_component_mut::<Position>(entity);
+    /// position.x = 5.0;
+    /// position.y = 5.0;
+    /// ```
+    pub fn get_component_mut<T>(&self, entity: Entity) -> RefMut<T>
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component,
+    {
+        self.check_alive(entity);
+        RefMut::map(self.storage_mut(), |storage| storage.get_mut(entity))
+    }
+
+    ///
+    /// Update a component from an entity via closure f.
+    ///
+    /// # Panics
+    ///
+    /// if entity has not the component
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();    
+    /// entity_manager.add_component::<Position>(entity);
+    ///
+    /// entity_manager.update_component_with::<Position, _>(entity, |position|{;
+    ///     position.x = 5.0;
+    ///     position.y = 5.0;
+    /// });
+    /// ```
+    pub fn update_component_with<T, F>(&self, entity: Entity, f: F)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+        F: FnOnce(&mut T),
+    {
+        f(&mut *self.get_component_mut::<T>(entity));
+        if let Some(hooks) = self.on_change.get(&TypeId::of::<T>()) {
+            for hook in hooks.iter() {
+                hook(self, entity);
+            }
+        }
+    }
+
+    ///
+    /// Register a closure called after each [`EntityManager::update_component_with`] of T.
+    ///
+    /// Other mutable accesses (e.g. [`EntityManager::get_component_mut`]) are not tracked.
+    /// The component is no longer borrowed when f is called.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct BoundingBox {
+    /// #     pub min_x: f32,
+    /// #     pub max_x: f32,
+    /// # }
+    /// #
+    /// # impl Component for BoundingBox {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position, BoundingBox });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// entity_manager.on_change::<Position, _>(|entity_manager, entity| {
+    ///     let x = entity_manager.get_component::<Position>(entity).x;
+    ///     let mut bounding_box = entity_manager.get_component_mut::<BoundingBox>(entity);
+    ///     bounding_box.min_x = x - 1.0;
+    ///     bounding_box.max_x = x + 1.0;
+    /// });
+    ///
+    /// let entity = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(entity);
+    /// entity_manager.add_component::<BoundingBox>(entity);
+    /// entity_manager.update_component_with::<Position, _>(entity, |position| position.x = 5.0);
+    /// assert_eq!(entity_manager.get_component::<BoundingBox>(entity).max_x, 6.0);
+    /// ```
+    pub fn on_change<T, F>(&mut self, f: F)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+        F: Fn(&EntityManager<EntityManagerComponentType>, Entity) + 'static,
+    {
+        self.on_change
+            .entry(TypeId::of::<T>())
+            .or_default()
+            .push(Rc::new(f));
+    }
+
+    ///
+    /// Get refs of several components from an entity.
+    ///
+    /// # Panics
+    ///
+    /// if entity has not one of the components
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Velocity {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Velocity {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position, Velocity });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(entity);
+    /// entity_manager.add_component::<Velocity>(entity);
+    ///
+    /// let (position, velocity) = entity_manager.components_of::<(Position, Velocity)>(entity);
+    /// println!("{}, {}", position.x + velocity.x, position.y + velocity.y);
+    /// ```
+    pub fn components_of<'a, C>(&'a self, entity: Entity) -> C::Refs
+    where
+        C: ComponentTuple<'a, EntityManagerComponentType>,
+    {
+        C::get(self, entity)
+    }
+
+    ///
+    /// Get refs of several components from an entity, `None` if entity has not one of the components.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Velocity {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Velocity {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position, Velocity });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(entity);
+    ///
+    /// assert!(entity_manager.try_components_of::<(Position,)>(entity).is_some());
+    /// assert!(entity_manager.try_components_of::<(Position, Velocity)>(entity).is_none());
+    /// ```
+    pub fn try_components_of<'a, C>(&'a self, entity: Entity) -> Option<C::Refs>
+    where
+        C: ComponentTuple<'a, EntityManagerComponentType>,
+    {
+        C::try_get(self, entity)
+    }
+
+    ///
+    /// Iterate over entities with all (required) components of the tuple C, with their refs.
+    ///
+    /// cf [`Opt`] for optional components.
+    pub fn iter_with<'a, C>(&'a self) -> impl Iterator<Item = (Entity, C::Refs)> + 'a
+    where
+        C: ComponentTuple<'a, EntityManagerComponentType>,
+    {
+        self.iter_all()
+            .filter(move |entity| C::has(self, *entity))
+            .map(move |entity| (entity, C::get(self, entity)))
+    }
+    ///
+    /// Iterate on Entity that match the query.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component, Query};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let e1 = entity_manager.create_entity();
+    /// entity_manager.add_component_with::<Position, _>(e1, |position|{;
+    ///     position.x = 5.0;
+    ///     position.y = 5.0;
+    /// });
+    /// let e2 = entity_manager.create_entity();
+    /// entity_manager.add_component_with::<Position, _>(e2, |position|{;
+    ///     position.x = 6.0;
+    ///     position.y = 6.0;
     /// });
+    ///
+    /// let mut query = Query::new();
+    /// query.check_component_by::<Position, _>(|position| -> bool {position.x > 5.5});
+    ///
+    /// println!("e1:{} e2:{}", e1.id, e2.id);
+    ///
+    /// for entity in entity_manager.iter(&query) {
+    ///     println!("entity:{}", entity.id);
+    /// }
+    /// ```
+    ///
+    /// The output will be :
+    /// ```text
+    /// e1:0 e2:1
+    /// entity:1
+    /// ```
+    pub fn iter<'a>(
+        &'a self,
+        query: &'a Query<EntityManagerComponentType>,
+    ) -> EntityIterator<EntityManagerComponentType> {
+        EntityIterator::new(query, self)
+    }
+
+    ///
+    /// Collect entities matching the query.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component, Query};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let e1 = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(e1);
+    /// entity_manager.create_entity();
+    ///
+    /// let mut query = Query::new();
+    /// query.check_component::<Position>();
+    /// assert_eq!(entity_manager.collect(&query), [e1]);
+    /// ```
+    pub fn collect(&self, query: &Query<EntityManagerComponentType>) -> Vec<Entity> {
+        let mut entities = Vec::with_capacity(self.allocator.len());
+        entities.extend(self.iter(query));
+        entities
+    }
+
+    ///
+    /// Collect copies of component `T` of entities matching the query.
+    ///
+    /// Entities without the component are skipped, no borrow is held once collected.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component, Query};
+    /// #
+    /// # #[derive(Default, Clone)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// for x in 0..3 {
+    ///     let entity = entity_manager.create_entity();
+    ///     entity_manager.add_component_with::<Position, _>(entity, |position| position.x = x as f32);
+    /// }
+    ///
+    /// let positions = entity_manager.collect_component::<Position>(&Query::new());
+    /// let center = positions.iter().map(|(_, position)| position.x).sum::<f32>() / positions.len() as f32;
+    /// assert_eq!(center, 1.0);
+    /// ```
+    pub fn collect_component<T>(
+        &self,
+        query: &Query<EntityManagerComponentType>,
+    ) -> Vec<(Entity, T)>
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + Clone,
+    {
+        self.iter(query)
+            .filter_map(|entity| {
+                self.try_get_component_cloned::<T>(entity)
+                    .map(|component| (entity, component))
+            })
+            .collect()
+    }
+
+    ///
+    /// Iterate over pairs of entities matching `left` and `right` queries (cartesian product).
+    ///
+    /// An entity matching both queries is paired with itself. Entities matching `right` are
+    /// collected once, when the iterator is created.
+    ///
+    /// It is O(n*m), pair it with a spatial pruning (e.g. in `left` or `right` queries)
+    /// when there are a lot of entities.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component, Query};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Bullet {}
+    /// #
+    /// # impl Component for Bullet {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Target {}
+    /// #
+    /// # impl Component for Target {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Bullet, Target });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let bullet = entity_manager.create_entity();
+    /// entity_manager.add_component::<Bullet>(bullet);
+    /// let target = entity_manager.create_entity();
+    /// entity_manager.add_component::<Target>(target);
+    ///
+    /// let bullets = Query::new().with_component::<Bullet>();
+    /// let targets = Query::new().with_component::<Target>();
+    /// for (bullet, target) in entity_manager.iter_join(&bullets, &targets) {
+    ///     println!("bullet:{} target:{}", bullet.id, target.id);
+    /// }
+    /// ```
+    pub fn iter_join<'a>(
+        &'a self,
+        left: &'a Query<EntityManagerComponentType>,
+        right: &Query<EntityManagerComponentType>,
+    ) -> impl Iterator<Item = (Entity, Entity)> + 'a {
+        let rights: Rc<[Entity]> = self.collect(right).into();
+        self.iter(left).flat_map(move |left| {
+            let rights = Rc::clone(&rights);
+            (0..rights.len()).map(move |pos| (left, rights[pos]))
+        })
+    }
+
+    ///
+    /// Get a read-only view, cf [`EntityView`].
+    pub fn as_view(&self) -> EntityView<EntityManagerComponentType> {
+        EntityView {
+            entity_manager: self,
+        }
+    }
+
+    ///
+    /// Call f with the component T of each Entity matching the query.
+    ///
+    /// Entities without the component T are skipped.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component, Query};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(entity);
+    ///
+    /// let mut query = Query::new();
+    /// query.check_component::<Position>();
+    ///
+    /// let mut sum = 0.0;
+    /// entity_manager.for_each::<Position, _>(&query, |_, position| sum += position.x);
+    /// ```
+    pub fn for_each<T, F>(&self, query: &Query<EntityManagerComponentType>, mut f: F)
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component,
+        F: FnMut(Entity, &T),
+    {
+        for entity in self.iter(query) {
+            if self.has_component::<T>(entity) {
+                f(entity, &*self.get_component::<T>(entity));
+            }
+        }
+    }
+
+    ///
+    /// Call f with the component T of each Entity matching the query (mutable version).
+    ///
+    /// Entities without the component T are skipped. The component is borrowed only
+    /// during the call of f, so the query could check it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component, Query};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(entity);
+    ///
+    /// let mut query = Query::new();
+    /// query.check_component::<Position>();
+    ///
+    /// entity_manager.for_each_mut::<Position, _>(&query, |_, position| position.x += 1.0);
     /// ```
-    pub fn add_component_with<T, F>(&self, entity: Entity, f: F)
+    pub fn for_each_mut<T, F>(&self, query: &Query<EntityManagerComponentType>, mut f: F)
     where
         EntityManagerComponentType: StorageAccess<T>,
         T: Component,
-        T::Storage: Storage<T>,
-        F: FnOnce(&mut T),
+        F: FnMut(Entity, &mut T),
     {
-        self.get_storage_mut().alloc(entity);
-        self.update_component_with(entity, f);
+        for entity in self.iter(query) {
+            if self.has_component::<T>(entity) {
+                f(entity, &mut *self.get_component_mut::<T>(entity));
+            }
+        }
     }
 
     ///
-    /// Remove component to an entity.
+    /// Transform in place the component T of each Entity matching the query.
+    ///
+    /// Same as [`EntityManager::for_each_mut`] without the entity.
     ///
     /// # Examples
     /// ```rust
-    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component, Query};
     /// #
     /// # #[derive(Default)]
-    /// # pub struct Position {
+    /// # pub struct Velocity {
     /// #     pub x: f32,
     /// #     pub y: f32,
     /// # }
     /// #
-    /// # impl Component for Position {
+    /// # impl Component for Velocity {
     /// #     type Storage = BasicVecStorage<Self>;
     /// # }
     /// #
-    /// # create_entity_manager_component!(EMC { Position });
+    /// # create_entity_manager_component!(EMC { Velocity });
     /// # type EntityManager = entity_system::EntityManager<EMC>;
     /// #
     /// let mut entity_manager = EntityManager::new();
     /// let entity = entity_manager.create_entity();
-    /// entity_manager.add_component::<Position>(entity);
+    /// entity_manager.add_component::<Velocity>(entity);
     ///
-    /// //
-    /// // lot of things
-    /// //
+    /// let mut query = Query::new();
+    /// query.check_component::<Velocity>();
     ///
-    /// entity_manager.remove_component::<Position>(entity);
+    /// // knockback
+    /// entity_manager.map_component::<Velocity, _>(&query, |velocity| velocity.x = -velocity.x);
     /// ```
-    pub fn remove_component<T>(&self, entity: Entity)
+    pub fn map_component<T, F>(&self, query: &Query<EntityManagerComponentType>, mut f: F)
     where
         EntityManagerComponentType: StorageAccess<T>,
         T: Component,
-        T::Storage: Storage<T>,
+        F: FnMut(&mut T),
     {
-        self.get_storage_mut().free(entity)
+        self.for_each_mut::<T, _>(query, |_, component| f(component));
     }
 
     ///
-    /// Check if an entity has a component.
+    /// Iterate on Entity that have (at least) all components of the tuple C.
+    ///
+    /// Only entities of matching archetypes (set of component types) are visited.
     ///
     /// # Examples
     /// ```rust
@@ -399,31 +2785,52 @@ where
     /// #     type Storage = BasicVecStorage<Self>;
     /// # }
     /// #
-    /// # create_entity_manager_component!(EMC { Position });
+    /// # #[derive(Default)]
+    /// # pub struct Velocity {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Velocity {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position, Velocity });
     /// # type EntityManager = entity_system::EntityManager<EMC>;
     /// #
     /// let mut entity_manager = EntityManager::new();
-    /// let entity = entity_manager.create_entity();
+    /// let e1 = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(e1);
+    /// let e2 = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(e2);
+    /// entity_manager.add_component::<Velocity>(e2);
     ///
-    /// assert!(!entity_manager.has_component::<Position>(entity));
-    /// entity_manager.add_component::<Position>(entity);
-    /// assert!(entity_manager.has_component::<Position>(entity));
+    /// println!("e1:{} e2:{}", e1.id, e2.id);
+    ///
+    /// for entity in entity_manager.iter_archetype::<(Position, Velocity)>() {
+    ///     println!("entity:{}", entity.id);
+    /// }
     /// ```
-    pub fn has_component<T>(&self, entity: Entity) -> bool
+    ///
+    /// The output will be :
+    /// ```text
+    /// e1:0 e2:1
+    /// entity:1
+    /// ```
+    pub fn iter_archetype<'a, C>(&'a self) -> impl Iterator<Item = Entity>
     where
-        EntityManagerComponentType: StorageAccess<T>,
-        T: Component,
-        T::Storage: Storage<T>,
+        C: ComponentTuple<'a, EntityManagerComponentType>,
     {
-        self.get_storage().has(entity)
+        self.archetypes
+            .borrow()
+            .entities(&C::type_ids())
+            .into_iter()
     }
-
-    ///
-    /// Get a ref of component from an entity.
     ///
-    /// # Panics
+    /// Get entities with the component `T`, in ascending id order.
     ///
-    /// if entity has not the component
+    /// Only the storage of `T` is walked, it is faster than a query with
+    /// [`Query::check_component`].
     ///
     /// # Examples
     /// ```rust
@@ -443,31 +2850,48 @@ where
     /// # type EntityManager = entity_system::EntityManager<EMC>;
     /// #
     /// let mut entity_manager = EntityManager::new();
-    /// let entity = entity_manager.create_entity();    
+    /// entity_manager.create_entity();
+    /// let entity = entity_manager.create_entity();
     /// entity_manager.add_component::<Position>(entity);
     ///
-    /// let position = entity_manager.get_component::<Position>(entity);
-    /// println!("{}, {}", position.x, position.y);
+    /// assert_eq!(entity_manager.entities_with::<Position>(), [entity]);
     /// ```
-    pub fn get_component<T>(&self, entity: Entity) -> Ref<T>
+    pub fn entities_with<T>(&self) -> Vec<Entity>
     where
         EntityManagerComponentType: StorageAccess<T>,
         T: Component,
-        T::Storage: Storage<T>,
     {
-        Ref::map(self.get_storage(), |storage| storage.get(entity))
+        let storage = self.storage::<T>();
+        let mut entities = Vec::with_capacity(storage.len());
+        storage.for_each(&mut |entity, _| entities.push(entity));
+        // pooled entities keep their components
+        if self.allocator.parked_len() > 0 {
+            entities.retain(|entity| self.allocator.is_alive(*entity));
+        }
+        entities
     }
 
     ///
-    /// Get a mut ref of component from an entity.
+    /// Iterate over entities with the component `T`, cf [`EntityManager::entities_with`].
     ///
-    /// # Panics
+    /// Entities are collected when the iterator is created, so the storage is not borrowed
+    /// while iterating.
+    pub fn iter_entities_with<T>(&self) -> impl Iterator<Item = Entity>
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component,
+    {
+        self.entities_with::<T>().into_iter()
+    }
+
     ///
-    /// if entity has not the component
+    /// Iterate over all Entities.
+    ///
+    /// Entities are yielded in ascending id order, whatever the creation and deletion order.
     ///
     /// # Examples
     /// ```rust
-    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component, Query};
     /// #
     /// # #[derive(Default)]
     /// # pub struct Position {
@@ -483,28 +2907,115 @@ where
     /// # type EntityManager = entity_system::EntityManager<EMC>;
     /// #
     /// let mut entity_manager = EntityManager::new();
-    /// let entity = entity_manager.create_entity();    
-    /// entity_manager.add_component::<Position>(entity);
+    /// let e1 = entity_manager.create_entity();
+    /// let e2 = entity_manager.create_entity();
     ///
-    /// let mut position = entity_manager.get_component_mut::<Position>(entity);
-    /// position.x = 5.0;
-    /// position.y = 5.0;
+    /// println!("e1:{} e2:{}", e1.id, e2.id);
+    ///
+    /// for entity in entity_manager.iter_all() {
+    ///     println!("entity:{}", entity.id);
+    /// }
     /// ```
-    pub fn get_component_mut<T>(&self, entity: Entity) -> RefMut<T>
+    ///
+    /// The output will be:
+    /// ```text
+    /// e1:0 e2:1
+    /// entity:0
+    /// entity:1
+    /// ```
+    pub fn iter_all(&self) -> EntityAllocatorIterator {
+        self.allocator.iter()
+    }
+
+    ///
+    /// Get the structural generation.
+    ///
+    /// It is incremented each time an entity is created or deleted
+    /// and each time a component is added or removed.
+    pub fn generation(&self) -> u64 {
+        self.generation.get()
+    }
+
+    ///
+    /// Get the current tick.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    ///
+    /// Increment the current tick (typically once per frame) and return it.
+    ///
+    /// Storages with change tracking record the current tick at each mutable access.
+    pub fn advance_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.components.set_tick(self.tick);
+        self.tick
+    }
+
+    ///
+    /// Iterate over entities (and their component) whose component has been added or
+    /// mutably accessed since `since_tick` (included).
+    ///
+    /// Component storage must support change tracking (cf [`crate::TrackedStorage`]).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, TrackedVecStorage, create_entity_manager_component};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = TrackedVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let mut entity_manager = EntityManager::new();
+    /// let e1 = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(e1);
+    /// let e2 = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(e2);
+    ///
+    /// let tick = entity_manager.advance_tick();
+    /// entity_manager.get_component_mut::<Position>(e2).x = 5.0;
+    ///
+    /// for (entity, position) in entity_manager.iter_changed::<Position>(tick) {
+    ///     println!("entity:{} x:{}", entity.id, position.x);
+    /// }
+    /// ```
+    ///
+    /// The output will be :
+    /// ```text
+    /// entity:1 x:5
+    /// ```
+    pub fn iter_changed<'a, T>(
+        &'a self,
+        since_tick: u64,
+    ) -> impl Iterator<Item = (Entity, Ref<'a, T>)> + 'a
     where
         EntityManagerComponentType: StorageAccess<T>,
-        T: Component,
-        T::Storage: Storage<T>,
+        T: Component + 'a,
+        <EntityManagerComponentType as StorageAccess<T>>::Storage: TrackedStorage<T>,
     {
-        RefMut::map(self.get_storage_mut(), |storage| storage.get_mut(entity))
+        self.iter_all()
+            .filter(move |entity| self.storage::<T>().changed_since(*entity, since_tick))
+            .map(move |entity| (entity, self.get_component::<T>(entity)))
     }
 
     ///
-    /// Update a component from an entity via closure f.
+    /// Call f on each component of type `T`, components are split across threads.
+    ///
+    /// Only available with the `rayon` feature. It requires `T: Send` as components are
+    /// mutated from rayon worker threads.
     ///
     /// # Panics
     ///
-    /// if entity has not the component
+    /// If the storage of `T` is already borrowed (cf [`EntityManager::storage_mut`]).
     ///
     /// # Examples
     /// ```rust
@@ -524,26 +3035,43 @@ where
     /// # type EntityManager = entity_system::EntityManager<EMC>;
     /// #
     /// let mut entity_manager = EntityManager::new();
-    /// let entity = entity_manager.create_entity();    
-    /// entity_manager.add_component::<Position>(entity);
+    /// for entity in entity_manager.create_entities(100) {
+    ///     entity_manager.add_component::<Position>(entity);
+    /// }
     ///
-    /// entity_manager.update_component_with::<Position, _>(entity, |position|{;
-    ///     position.x = 5.0;
-    ///     position.y = 5.0;
-    /// });
+    /// entity_manager.par_iter_component_mut::<Position>(|_, position| position.x += 1.0);
     /// ```
-    pub fn update_component_with<T, F>(&self, entity: Entity, f: F)
+    #[cfg(feature = "rayon")]
+    pub fn par_iter_component_mut<T>(&self, f: impl Fn(Entity, &mut T) + Sync)
     where
         EntityManagerComponentType: StorageAccess<T>,
-        T: Component,
-        T::Storage: Storage<T>,
-        F: FnOnce(&mut T),
+        T: Component + Send,
+        <EntityManagerComponentType as StorageAccess<T>>::Storage: ParStorage<T>,
     {
-        f(&mut *self.get_component_mut::<T>(entity));
+        let allocator = &self.allocator;
+        let mut storage = self.storage_mut::<T>();
+        // pooled entities keep their components
+        if allocator.parked_len() > 0 {
+            storage.par_for_each_mut(&|entity, component| {
+                if allocator.is_alive(entity) {
+                    f(entity, component)
+                }
+            });
+        } else {
+            storage.par_for_each_mut(&f);
+        }
     }
 
     ///
-    /// Iterate on Entity that match the query.
+    /// Get a ref on the storage of a component.
+    ///
+    /// Useful to borrow the storage only once in a loop.
+    ///
+    /// # Panics
+    ///
+    /// The storage is borrowed until the ref is dropped: meanwhile, any mutable access to
+    /// this component (`storage_mut`, `get_component_mut`, `update_component_with`,
+    /// `add_component`, `remove_component`, ...) panics.
     ///
     /// # Examples
     /// ```rust
@@ -562,42 +3090,39 @@ where
     /// # create_entity_manager_component!(EMC { Position });
     /// # type EntityManager = entity_system::EntityManager<EMC>;
     /// #
+    /// use entity_system::Storage;
+    ///
     /// let mut entity_manager = EntityManager::new();
-    /// let e1 = entity_manager.create_entity();
-    /// entity_manager.add_component_with::<Position, _>(e1, |position|{;
-    ///     position.x = 5.0;
-    ///     position.y = 5.0;
-    /// });
-    /// let e2 = entity_manager.create_entity();
-    /// entity_manager.add_component_with::<Position, _>(e2, |position|{;
-    ///     position.x = 6.0;
-    ///     position.y = 6.0;
-    /// });
+    /// let entity = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(entity);
     ///
     /// let mut query = Query::new();
-    /// query.check_component_by::<Position, _>(|position| -> bool {position.x > 5.5});
-    ///
-    /// println!("e1:{} e2:{}", e1.id, e2.id);
+    /// query.check_component::<Position>();
     ///
+    /// let storage = entity_manager.storage::<Position>();
     /// for entity in entity_manager.iter(&query) {
-    ///     println!("entity:{}", entity.id);
+    ///     println!("{}", storage.get(entity).x);
     /// }
     /// ```
-    ///
-    /// The output will be :
-    /// ```text
-    /// e1:0 e2:1
-    /// entity:1
-    /// ```
-    pub fn iter<'a>(
-        &'a self,
-        query: &'a Query<EntityManagerComponentType>,
-    ) -> EntityIterator<EntityManagerComponentType> {
-        EntityIterator::new(query, self)
+    pub fn storage<T>(&self) -> Ref<<EntityManagerComponentType as StorageAccess<T>>::Storage>
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component,
+    {
+        self.components.get()
     }
 
     ///
-    /// Iterate over all Entities.
+    /// Get a mut ref on the storage of a component.
+    ///
+    /// Adding or removing components through the storage bypasses the entity manager
+    /// bookkeeping (cf [`EntityManager::generation`], [`EntityManager::iter_archetype`]),
+    /// prefer [`EntityManager::add_component`] and [`EntityManager::remove_component`].
+    ///
+    /// # Panics
+    ///
+    /// If the storage is already borrowed. The storage is borrowed until the ref is dropped:
+    /// meanwhile, any access to this component (including queries checking it) panics.
     ///
     /// # Examples
     /// ```rust
@@ -616,43 +3141,236 @@ where
     /// # create_entity_manager_component!(EMC { Position });
     /// # type EntityManager = entity_system::EntityManager<EMC>;
     /// #
-    /// let mut entity_manager = EntityManager::new();
-    /// let e1 = entity_manager.create_entity();
-    /// let e2 = entity_manager.create_entity();
+    /// use entity_system::Storage;
     ///
-    /// println!("e1:{} e2:{}", e1.id, e2.id);
+    /// let mut entity_manager = EntityManager::new();
+    /// let entity = entity_manager.create_entity();
+    /// entity_manager.add_component::<Position>(entity);
     ///
+    /// let mut storage = entity_manager.storage_mut::<Position>();
     /// for entity in entity_manager.iter_all() {
-    ///     println!("entity:{}", entity.id);
+    ///     storage.get_mut(entity).x += 1.0;
     /// }
     /// ```
-    ///
-    /// The output will be:
-    /// ```text
-    /// e1:0 e2:1
-    /// entity:0
-    /// entity:1
-    /// ```
-    pub fn iter_all(&self) -> EntityAllocatorIterator {
-        self.allocator.iter()
+    pub fn storage_mut<T>(
+        &self,
+    ) -> RefMut<<EntityManagerComponentType as StorageAccess<T>>::Storage>
+    where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component,
+    {
+        self.components.get_mut()
+    }
+
+    fn created(&mut self, entity: Entity) {
+        if let Some(on_create) = self.on_create.as_mut() {
+            on_create(entity);
+        }
+    }
+
+    fn check_alive(&self, entity: Entity) {
+        assert!(
+            self.allocator.is_alive(entity),
+            "entity {} is not alive",
+            entity.id
+        );
+    }
+
+    fn component_stats(&self) -> Vec<(&'static str, Option<usize>)> {
+        // pooled entities keep their components
+        let allocator = &self.allocator;
+        let alive = |entity| allocator.is_alive(entity);
+        let alive: Option<&dyn Fn(Entity) -> bool> = if allocator.parked_len() > 0 {
+            Some(&alive)
+        } else {
+            None
+        };
+        self.components.stats(alive)
+    }
+
+    fn touch(&self) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+    }
+
+    fn init_component<T>(
+        &self,
+        storage: &mut <EntityManagerComponentType as StorageAccess<T>>::Storage,
+        entity: Entity,
+    ) where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + 'static,
+    {
+        let prototype = self
+            .prototypes
+            .get(&TypeId::of::<T>())
+            .and_then(|prototype| prototype.downcast_ref::<Prototype<T>>());
+        if let Some(init) = prototype {
+            init(storage.get_mut(entity));
+        }
+    }
+
+    fn on_component_added<T>(&self, entity: Entity)
+    where
+        T: 'static,
+    {
+        self.touch();
+        self.archetypes.borrow_mut().add(entity, TypeId::of::<T>());
+    }
+
+    fn on_component_removed<T>(&self, entity: Entity)
+    where
+        T: 'static,
+    {
+        self.touch();
+        self.archetypes
+            .borrow_mut()
+            .remove(entity, TypeId::of::<T>());
+    }
+}
+
+impl<EntityManagerComponentType> fmt::Debug for EntityManager<EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // mutably borrowed storages are not counted, so it could be used anywhere
+        let components: Vec<_> = self
+            .component_stats()
+            .into_iter()
+            .map(|(name, count)| (name, DebugCount(count)))
+            .collect();
+        f.debug_struct("EntityManager")
+            .field("entities", &self.allocator.len())
+            .field("components", &components)
+            .finish()
+    }
+}
+
+struct DebugCount(Option<usize>);
+
+impl fmt::Debug for DebugCount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(count) => write!(f, "{}", count),
+            None => write!(f, "<borrowed>"),
+        }
+    }
+}
+
+impl<'a, EntityManagerComponentType> IntoIterator for &'a EntityManager<EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default,
+{
+    type Item = Entity;
+    type IntoIter = EntityAllocatorIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_all()
+    }
+}
+
+///
+/// Read-only view of an [`EntityManager`].
+///
+/// No entity or component could be created, removed or mutated through it
+/// (e.g. to be given to render systems).
+///
+/// # Examples
+/// ```rust
+/// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component, Query};
+/// #
+/// # #[derive(Default)]
+/// # pub struct Position {
+/// #     pub x: f32,
+/// #     pub y: f32,
+/// # }
+/// #
+/// # impl Component for Position {
+/// #     type Storage = BasicVecStorage<Self>;
+/// # }
+/// #
+/// # create_entity_manager_component!(EMC { Position });
+/// # type EntityManager = entity_system::EntityManager<EMC>;
+/// type EntityView<'a> = entity_system::EntityView<'a, EMC>;
+///
+/// fn draw(view: EntityView) {
+///     let mut query = Query::new();
+///     query.check_component::<Position>();
+///     for entity in view.iter(&query) {
+///         let position = view.get_component::<Position>(entity);
+///         println!("{}, {}", position.x, position.y);
+///     }
+/// }
+///
+/// let mut entity_manager = EntityManager::new();
+/// let entity = entity_manager.create_entity();
+/// entity_manager.add_component::<Position>(entity);
+/// draw(entity_manager.as_view());
+/// ```
+pub struct EntityView<'a, EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default,
+{
+    entity_manager: &'a EntityManager<EntityManagerComponentType>,
+}
+
+impl<'a, EntityManagerComponentType> Clone for EntityView<'a, EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default,
+{
+    fn clone(&self) -> Self {
+        *self
     }
+}
+
+impl<'a, EntityManagerComponentType> Copy for EntityView<'a, EntityManagerComponentType> where
+    EntityManagerComponentType: EntityManagerComponent + Default
+{
+}
 
-    fn get_storage<T>(&self) -> Ref<<T as Component>::Storage>
+impl<'a, EntityManagerComponentType> EntityView<'a, EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default,
+{
+    ///
+    /// Cf [`EntityManager::has_component`]
+    pub fn has_component<T>(&self, entity: Entity) -> bool
     where
         EntityManagerComponentType: StorageAccess<T>,
         T: Component,
-        T::Storage: Storage<T>,
     {
-        self.components.get()
+        self.entity_manager.has_component::<T>(entity)
     }
 
-    fn get_storage_mut<T>(&self) -> RefMut<<T as Component>::Storage>
+    ///
+    /// Cf [`EntityManager::get_component`]
+    pub fn get_component<T>(&self, entity: Entity) -> Ref<'a, T>
     where
         EntityManagerComponentType: StorageAccess<T>,
         T: Component,
-        T::Storage: Storage<T>,
     {
-        self.components.get_mut()
+        self.entity_manager.get_component::<T>(entity)
+    }
+
+    ///
+    /// Cf [`EntityManager::iter`]
+    pub fn iter(
+        &self,
+        query: &'a Query<EntityManagerComponentType>,
+    ) -> EntityIterator<'a, EntityManagerComponentType> {
+        self.entity_manager.iter(query)
+    }
+
+    ///
+    /// Cf [`EntityManager::iter_all`]
+    pub fn iter_all(&self) -> EntityAllocatorIterator<'a> {
+        self.entity_manager.iter_all()
+    }
+
+    ///
+    /// Count entities matching the query.
+    pub fn count(&self, query: &Query<EntityManagerComponentType>) -> usize {
+        self.entity_manager.iter(query).count()
     }
 }
 
@@ -712,7 +3430,7 @@ where
 }
 
 type Filter<EntityManagerComponentType> =
-    Box<dyn Fn(&EntityManager<EntityManagerComponentType>, Entity) -> bool>;
+    Rc<dyn Fn(&EntityManager<EntityManagerComponentType>, Entity) -> bool>;
 
 ///
 /// Query to select some [`Entity`] from [`EntityManager`].
@@ -752,6 +3470,11 @@ type Filter<EntityManagerComponentType> =
 /// query.check_component::<Position>();
 ///
 /// //
+/// // select entity with component Position and Velocity
+/// let mut query = Query::new();
+/// query.require::<(Position, Velocity)>();
+///
+/// //
 /// // select entity without component Position
 /// let mut query = Query::new();
 /// query.check_not_component::<Position>();
@@ -778,6 +3501,13 @@ type Filter<EntityManagerComponentType> =
 ///     
 ///     pos_x + vel_x > 5.0
 /// });
+///
+/// //
+/// // derive a query from another one, filters are shared
+/// let mut base = Query::new();
+/// base.check_component::<Position>();
+/// let mut query = base.clone();
+/// query.check_component::<Velocity>();
 /// ```
 #[derive(Default)]
 pub struct Query<EntityManagerComponentType>
@@ -785,6 +3515,19 @@ where
     EntityManagerComponentType: EntityManagerComponent + Default,
 {
     filters: Vec<Filter<EntityManagerComponentType>>,
+    volatile: bool,
+}
+
+impl<EntityManagerComponentType> Clone for Query<EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default,
+{
+    fn clone(&self) -> Self {
+        Self {
+            filters: self.filters.clone(),
+            volatile: self.volatile,
+        }
+    }
 }
 
 impl<EntityManagerComponentType> Query<EntityManagerComponentType>
@@ -796,6 +3539,7 @@ where
     pub fn new() -> Self {
         Self {
             filters: Vec::new(),
+            volatile: false,
         }
     }
 
@@ -822,12 +3566,24 @@ where
     where
         EntityManagerComponentType: StorageAccess<C>,
         C: Component,
-        C::Storage: Storage<C>,
     {
-        self.filters
-            .push(Box::new(|entity_manager, entity| -> bool {
-                entity_manager.has_component::<C>(entity)
-            }));
+        self.filters.push(Rc::new(|entity_manager, entity| -> bool {
+            entity_manager.has_component::<C>(entity)
+        }));
+        self
+    }
+
+    ///
+    /// Check entity has all components of the tuple C.
+    ///
+    /// Same as [`Query::check_component`] for each component.
+    pub fn require<C>(&mut self) -> &mut Self
+    where
+        C: for<'a> ComponentTuple<'a, EntityManagerComponentType> + 'static,
+    {
+        self.filters.push(Rc::new(|entity_manager, entity| -> bool {
+            C::has(entity_manager, entity)
+        }));
         self
     }
 
@@ -837,12 +3593,19 @@ where
     where
         EntityManagerComponentType: StorageAccess<C>,
         C: Component,
-        C::Storage: Storage<C>,
     {
-        self.filters
-            .push(Box::new(|entity_manager, entity| -> bool {
-                !entity_manager.has_component::<C>(entity)
-            }));
+        self.filters.push(Rc::new(|entity_manager, entity| -> bool {
+            !entity_manager.has_component::<C>(entity)
+        }));
+        self
+    }
+
+    ///
+    /// Check entity id is in the range.
+    pub fn check_id_range(&mut self, range: Range<u32>) -> &mut Self {
+        self.filters.push(Rc::new(move |_, entity| -> bool {
+            range.contains(&entity.id)
+        }));
         self
     }
 
@@ -852,14 +3615,65 @@ where
     where
         EntityManagerComponentType: StorageAccess<C>,
         C: Component,
-        C::Storage: Storage<C>,
         F: Fn(&C) -> bool + 'static,
     {
+        self.volatile = true;
+        self.filters
+            .push(Rc::new(move |entity_manager, entity| -> bool {
+                if entity_manager.has_component::<C>(entity) {
+                    let compostant = entity_manager.get_component::<C>(entity);
+                    f(&*compostant)
+                } else {
+                    false
+                }
+            }));
+        self
+    }
+
+    ///
+    /// Check the component match the closure f, the default value is given to f
+    /// if entity has not the component.
+    pub fn check_component_or<C, F>(&mut self, f: F) -> &mut Self
+    where
+        EntityManagerComponentType: StorageAccess<C>,
+        C: Component + Default,
+        F: Fn(&C) -> bool + 'static,
+    {
+        self.volatile = true;
         self.filters
-            .push(Box::new(move |entity_manager, entity| -> bool {
+            .push(Rc::new(move |entity_manager, entity| -> bool {
                 if entity_manager.has_component::<C>(entity) {
                     let compostant = entity_manager.get_component::<C>(entity);
                     f(&*compostant)
+                } else {
+                    f(&C::default())
+                }
+            }));
+        self
+    }
+
+    ///
+    /// Check entity has the component and the composant match the closure f,
+    /// the component is mutably borrowed by f.
+    ///
+    /// Side effects of f are intentional (e.g. update a cached field before testing it),
+    /// but they depend on filters order and are applied each time the query is checked.
+    ///
+    /// # Panics
+    ///
+    /// If the component is borrowed while the query is checked.
+    pub fn check_component_mut_by<C, F>(&mut self, f: F) -> &mut Self
+    where
+        EntityManagerComponentType: StorageAccess<C>,
+        C: Component,
+        F: Fn(&mut C) -> bool + 'static,
+    {
+        self.volatile = true;
+        self.filters
+            .push(Rc::new(move |entity_manager, entity| -> bool {
+                if entity_manager.has_component::<C>(entity) {
+                    let mut compostant = entity_manager.get_component_mut::<C>(entity);
+                    f(&mut *compostant)
                 } else {
                     false
                 }
@@ -867,13 +3681,246 @@ where
         self
     }
 
+    ///
+    /// Check entity component has been added or mutably accessed since the tick `since` (included).
+    ///
+    /// Only available for components with change tracking, cf [`EntityManager::iter_changed`].
+    pub fn check_changed<C>(&mut self, since: u64) -> &mut Self
+    where
+        EntityManagerComponentType: StorageAccess<C>,
+        C: Component,
+        <EntityManagerComponentType as StorageAccess<C>>::Storage: TrackedStorage<C>,
+    {
+        self.volatile = true;
+        self.filters
+            .push(Rc::new(move |entity_manager, entity| -> bool {
+                entity_manager.storage::<C>().changed_since(entity, since)
+            }));
+        self
+    }
+
     ///
     /// Check if entity match the closure f.
     pub fn check_global<F>(&mut self, f: F) -> &mut Self
     where
         F: Fn(&EntityManager<EntityManagerComponentType>, Entity) -> bool + 'static,
     {
-        self.filters.push(Box::new(f));
+        self.volatile = true;
+        self.filters.push(Rc::new(f));
+        self
+    }
+
+    ///
+    /// Check if entity does not match the closure f.
+    pub fn check_not_global<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&EntityManager<EntityManagerComponentType>, Entity) -> bool + 'static,
+    {
+        self.volatile = true;
+        self.filters
+            .push(Rc::new(move |entity_manager, entity| -> bool {
+                !f(entity_manager, entity)
+            }));
+        self
+    }
+
+    ///
+    /// Check if query depends on component values.
+    ///
+    /// It is the case when [`Query::check_component_by`], [`Query::check_component_or`],
+    /// [`Query::check_component_mut_by`], [`Query::check_changed`], [`Query::check_global`]
+    /// or [`Query::check_not_global`] has been used.
+    pub fn is_volatile(&self) -> bool {
+        self.volatile
+    }
+
+    ///
+    /// Get the number of filters.
+    pub fn len(&self) -> usize {
+        self.filters.len()
+    }
+
+    ///
+    /// Check if the query has no filter (it matches all entities).
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    ///
+    /// Remove all filters, keeping their allocation to rebuild the query.
+    pub fn clear(&mut self) -> &mut Self {
+        self.filters.clear();
+        self.volatile = false;
+        self
+    }
+
+    ///
+    /// Same as [`Query::check_component`], by value (builder style).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component, Query};
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Position {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Position {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # #[derive(Default)]
+    /// # pub struct Velocity {
+    /// #     pub x: f32,
+    /// #     pub y: f32,
+    /// # }
+    /// #
+    /// # impl Component for Velocity {
+    /// #     type Storage = BasicVecStorage<Self>;
+    /// # }
+    /// #
+    /// # create_entity_manager_component!(EMC { Position, Velocity });
+    /// # type EntityManager = entity_system::EntityManager<EMC>;
+    /// #
+    /// let entity_manager = EntityManager::new();
+    /// let query = Query::new()
+    ///     .with_component::<Position>()
+    ///     .with_component_by::<Velocity, _>(|velocity| velocity.x > 0.0)
+    ///     .build();
+    /// assert_eq!(entity_manager.iter(&query).count(), 0);
+    /// ```
+    pub fn with_component<C>(mut self) -> Self
+    where
+        EntityManagerComponentType: StorageAccess<C>,
+        C: Component,
+    {
+        self.check_component::<C>();
+        self
+    }
+
+    ///
+    /// Same as [`Query::check_not_component`], by value (builder style).
+    pub fn with_not_component<C>(mut self) -> Self
+    where
+        EntityManagerComponentType: StorageAccess<C>,
+        C: Component,
+    {
+        self.check_not_component::<C>();
+        self
+    }
+
+    ///
+    /// Same as [`Query::check_component_by`], by value (builder style).
+    pub fn with_component_by<C, F>(mut self, f: F) -> Self
+    where
+        EntityManagerComponentType: StorageAccess<C>,
+        C: Component,
+        F: Fn(&C) -> bool + 'static,
+    {
+        self.check_component_by::<C, F>(f);
+        self
+    }
+
+    ///
+    /// Same as [`Query::check_global`], by value (builder style).
+    pub fn with_global<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&EntityManager<EntityManagerComponentType>, Entity) -> bool + 'static,
+    {
+        self.check_global(f);
+        self
+    }
+
+    ///
+    /// End of builder style construction, return the query.
+    pub fn build(self) -> Self {
         self
     }
 }
+
+///
+/// [`Query`] with a cache of matching entities.
+///
+/// The cache is refreshed only when [`EntityManager::generation`] changed since the
+/// last evaluation. A volatile query (cf [`Query::is_volatile`]) is always re-evaluated.
+///
+/// ## Examples
+/// ```rust
+/// # use entity_system::{Component, BasicVecStorage, create_entity_manager_component};
+/// #
+/// # #[derive(Default)]
+/// # pub struct Position {
+/// #     pub x: f32,
+/// #     pub y: f32,
+/// # }
+/// #
+/// # impl Component for Position {
+/// #     type Storage = BasicVecStorage<Self>;
+/// # }
+/// #
+/// # create_entity_manager_component!(EMC { Position });
+/// # type EntityManager = entity_system::EntityManager<EMC>;
+/// # type Query = entity_system::Query<EMC>;
+/// # type CachedQuery = entity_system::CachedQuery<EMC>;
+/// #
+/// let mut entity_manager = EntityManager::new();
+/// let entity = entity_manager.create_entity();
+/// entity_manager.add_component::<Position>(entity);
+///
+/// let mut query = Query::new();
+/// query.check_component::<Position>();
+/// let mut cached_query = CachedQuery::new(query);
+///
+/// assert_eq!(cached_query.entities(&entity_manager), &[entity]);
+/// ```
+pub struct CachedQuery<EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default,
+{
+    query: Query<EntityManagerComponentType>,
+    entities: Vec<Entity>,
+    generation: Option<u64>,
+}
+
+impl<EntityManagerComponentType> CachedQuery<EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default,
+{
+    ///
+    /// Create a new cached query from a query
+    pub fn new(query: Query<EntityManagerComponentType>) -> Self {
+        Self {
+            query,
+            entities: Vec::new(),
+            generation: None,
+        }
+    }
+
+    ///
+    /// Get the underlying query
+    pub fn query(&self) -> &Query<EntityManagerComponentType> {
+        &self.query
+    }
+
+    ///
+    /// Check if the cache must be refreshed.
+    pub fn is_dirty(&self, entity_manager: &EntityManager<EntityManagerComponentType>) -> bool {
+        self.query.is_volatile() || self.generation != Some(entity_manager.generation())
+    }
+
+    ///
+    /// Get entities matching the query, refresh the cache if needed.
+    pub fn entities(
+        &mut self,
+        entity_manager: &EntityManager<EntityManagerComponentType>,
+    ) -> &[Entity] {
+        if self.is_dirty(entity_manager) {
+            self.entities.clear();
+            self.entities.extend(entity_manager.iter(&self.query));
+            self.generation = Some(entity_manager.generation());
+        }
+        &self.entities
+    }
+}
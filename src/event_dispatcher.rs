@@ -1,53 +1,123 @@
-use std::cell::RefCell;
-use std::collections::VecDeque;
+use std::any::{Any, TypeId};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
 use std::marker::PhantomData;
 use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 pub trait EventHandler<EventType> {
     fn on_event(&mut self, event: &EventType);
 }
 
+pub trait ContextEventHandler<EventType, Context> {
+    fn on_event_with(&mut self, event: &EventType, context: &Context);
+}
+
+impl<EventHandlerType, EventType> ContextEventHandler<EventType, ()> for EventHandlerType
+where
+    EventHandlerType: EventHandler<EventType> + ?Sized,
+{
+    fn on_event_with(&mut self, event: &EventType, _context: &()) {
+        self.on_event(event);
+    }
+}
+
+pub struct FnEventHandler<EventType> {
+    f: Box<dyn FnMut(&EventType)>,
+}
+
+impl<EventType> FnEventHandler<EventType> {
+    pub fn new<F>(f: F) -> Self
+    where
+        F: FnMut(&EventType) + 'static,
+    {
+        Self { f: Box::new(f) }
+    }
+}
+
+impl<EventType> EventHandler<EventType> for FnEventHandler<EventType> {
+    fn on_event(&mut self, event: &EventType) {
+        (self.f)(event);
+    }
+}
+
 pub trait Dispatcher<EventAdapters> {
+    type Context;
+
+    /// Connect `handler`, kept alive by the dispatcher until disconnected.
     fn connect<EventHandlerType, EventType>(
         self: &Rc<Self>,
         handler: Rc<RefCell<EventHandlerType>>,
     ) where
-        EventHandlerType: EventHandler<EventType> + 'static,
-        EventAdapters: AccessEventAdapter<EventType>,
+        EventHandlerType: ContextEventHandler<EventType, Self::Context> + 'static,
+        EventAdapters: AccessEventAdapter<EventType, Self::Context>,
         EventType: 'static;
 
+    /// Connect `handler` without keeping it alive: it is no longer invoked once dropped
+    /// by its owner (used by `Connection::connect`).
+    ///
+    /// By default, it is the same as `connect`.
+    fn connect_weak<EventHandlerType, EventType>(
+        self: &Rc<Self>,
+        handler: Rc<RefCell<EventHandlerType>>,
+    ) where
+        EventHandlerType: ContextEventHandler<EventType, Self::Context> + 'static,
+        EventAdapters: AccessEventAdapter<EventType, Self::Context>,
+        EventType: 'static,
+    {
+        self.connect::<EventHandlerType, EventType>(handler);
+    }
+
     fn disconnect<EventHandlerType, EventType>(
         self: &Rc<Self>,
         handler: Rc<RefCell<EventHandlerType>>,
     ) where
-        EventHandlerType: EventHandler<EventType> + 'static,
-        EventAdapters: AccessEventAdapter<EventType>,
+        EventHandlerType: ContextEventHandler<EventType, Self::Context> + 'static,
+        EventAdapters: AccessEventAdapter<EventType, Self::Context>,
         EventType: 'static;
 }
 
-pub trait AccessEventAdapter<EventType> {
-    fn get(&self) -> &RefCell<Adapter<EventType>>;
+pub trait AccessEventAdapter<EventType, Context = ()> {
+    fn get(&self) -> &RefCell<Adapter<EventType, Context>>;
 }
 
 impl<DispatcherType, EventAdapters, EventHandlerType, EventType> Default
     for Connection<DispatcherType, EventAdapters, EventHandlerType, EventType>
 where
     EventType: 'static,
-    EventHandlerType: EventHandler<EventType> + 'static,
+    EventHandlerType: ContextEventHandler<EventType, DispatcherType::Context> + 'static,
     DispatcherType: Dispatcher<EventAdapters>,
-    EventAdapters: AccessEventAdapter<EventType>,
+    EventAdapters: AccessEventAdapter<EventType, DispatcherType::Context>,
 {
     fn default() -> Self {
         Self::empty()
     }
 }
 
+impl<DispatcherType, EventAdapters, EventHandlerType, EventType> Clone
+    for Connection<DispatcherType, EventAdapters, EventHandlerType, EventType>
+where
+    EventType: 'static,
+    EventHandlerType: ContextEventHandler<EventType, DispatcherType::Context> + 'static,
+    DispatcherType: Dispatcher<EventAdapters>,
+    EventAdapters: AccessEventAdapter<EventType, DispatcherType::Context>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            dispatcher: Weak::clone(&self.dispatcher),
+            handler: Weak::clone(&self.handler),
+            event: PhantomData,
+            adapters: PhantomData,
+        }
+    }
+}
+
 pub struct Connection<DispatcherType, EventAdapters, EventHandlerType, EventType>
 where
     EventType: 'static,
-    EventHandlerType: EventHandler<EventType> + 'static,
+    EventHandlerType: ContextEventHandler<EventType, DispatcherType::Context> + 'static,
     DispatcherType: Dispatcher<EventAdapters>,
-    EventAdapters: AccessEventAdapter<EventType>,
+    EventAdapters: AccessEventAdapter<EventType, DispatcherType::Context>,
 {
     dispatcher: Weak<DispatcherType>,
     handler: Weak<RefCell<EventHandlerType>>,
@@ -59,9 +129,9 @@ impl<DispatcherType, EventAdapters, EventHandlerType, EventType>
     Connection<DispatcherType, EventAdapters, EventHandlerType, EventType>
 where
     EventType: 'static,
-    EventHandlerType: EventHandler<EventType> + 'static,
+    EventHandlerType: ContextEventHandler<EventType, DispatcherType::Context> + 'static,
     DispatcherType: Dispatcher<EventAdapters>,
-    EventAdapters: AccessEventAdapter<EventType>,
+    EventAdapters: AccessEventAdapter<EventType, DispatcherType::Context>,
 {
     pub fn new(dispatcher: &Rc<DispatcherType>, handler: &Rc<RefCell<EventHandlerType>>) -> Self {
         Self {
@@ -81,11 +151,13 @@ where
         }
     }
 
+    /// Connect the handler weakly: the connection is dropped with the handler,
+    /// cf `Dispatcher::connect_weak`.
     pub fn connect(&self) {
         if let (Some(dispatcher), Some(handler)) =
             (self.dispatcher.upgrade(), self.handler.upgrade())
         {
-            dispatcher.connect::<EventHandlerType, EventType>(handler);
+            dispatcher.connect_weak::<EventHandlerType, EventType>(handler);
         }
     }
 
@@ -98,83 +170,378 @@ where
     }
 }
 
-#[derive(Default)]
-pub struct Adapter<EventType> {
-    handlers: Vec<Rc<RefCell<dyn EventHandler<EventType>>>>,
+type HandlerType<EventType, Context> = Rc<RefCell<dyn ContextEventHandler<EventType, Context>>>;
+type WeakHandlerType<EventType, Context> =
+    Weak<RefCell<dyn ContextEventHandler<EventType, Context>>>;
+type PredicateType<EventType> = Rc<dyn Fn(&EventType) -> bool>;
+type HandlerEntry<EventType, Context> = (
+    WeakHandlerType<EventType, Context>,
+    Option<PredicateType<EventType>>,
+);
+
+/// Handlers connected by `connect` are kept alive until disconnected.
+/// Handlers connected by `connect_weak` (e.g. by `Connection::connect`) are held weakly:
+/// a handler dropped by its owner is no longer invoked.
+///
+/// Handlers connected by `connect_filtered` are invoked only for events matching their predicate.
+pub struct Adapter<EventType, Context = ()> {
+    handlers: Vec<HandlerEntry<EventType, Context>>,
+    owned: Vec<HandlerType<EventType, Context>>,
 }
 
-impl<EventType> Adapter<EventType> {
+impl<EventType, Context> Default for Adapter<EventType, Context> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<EventType, Context> Adapter<EventType, Context> {
     pub fn new() -> Self {
         Self {
             handlers: Vec::new(),
+            owned: Vec::new(),
         }
     }
 
-    pub fn connect(&mut self, handler: Rc<RefCell<dyn EventHandler<EventType>>>) {
-        self.handlers.push(handler);
+    pub fn connect(&mut self, handler: HandlerType<EventType, Context>) {
+        self.handlers.push((Rc::downgrade(&handler), None));
+        self.owned.push(handler);
     }
 
-    pub fn disconnect(&mut self, handler: Rc<RefCell<dyn EventHandler<EventType>>>) {
-        if let Some(pos) = self
-            .handlers
-            .iter()
-            .position(|x| std::ptr::eq(x.as_ptr() as *const (), handler.as_ptr() as *const ()))
-        {
+    pub fn connect_weak(&mut self, handler: HandlerType<EventType, Context>) {
+        self.handlers.push((Rc::downgrade(&handler), None));
+    }
+
+    pub fn connect_filtered(
+        &mut self,
+        handler: HandlerType<EventType, Context>,
+        predicate: PredicateType<EventType>,
+    ) {
+        self.handlers
+            .push((Rc::downgrade(&handler), Some(predicate)));
+    }
+
+    pub fn disconnect(&mut self, handler: HandlerType<EventType, Context>) {
+        if let Some(pos) = self.position(&handler) {
             self.handlers.remove(pos);
         }
+        self.owned.retain(|x| !Rc::ptr_eq(x, &handler));
+    }
+
+    pub fn clear(&mut self) {
+        self.handlers.clear();
+        self.owned.clear();
+    }
+
+    pub fn contains(&self, handler: &HandlerType<EventType, Context>) -> bool {
+        self.position(handler).is_some()
+    }
+
+    fn position(&self, handler: &HandlerType<EventType, Context>) -> Option<usize> {
+        self.handlers.iter().position(|(x, _)| {
+            x.strong_count() > 0
+                && std::ptr::eq(x.as_ptr() as *const (), Rc::as_ptr(handler) as *const ())
+        })
+    }
+
+    /// Handlers are snapshotted before the call: handlers connected or disconnected
+    /// meanwhile are taken into account at next invocation.
+    ///
+    /// Dropped handlers are removed, handlers whose predicate rejects the event are skipped.
+    pub fn invoke(&mut self, event: &EventType, context: &Context) {
+        for handler in self.snapshot(event) {
+            handler.borrow_mut().on_event_with(event, context);
+        }
     }
 
-    pub fn invoke(&mut self, event: &EventType) {
-        for handler in self.handlers.iter() {
-            handler.borrow_mut().on_event(event);
+    ///
+    /// Same as `invoke`, but the adapter is not borrowed while handlers run: they can
+    /// use the adapter of the same event type (e.g. `EventDispatcher::is_connected`).
+    pub fn invoke_shared(adapter: &RefCell<Self>, event: &EventType, context: &Context) {
+        let handlers = adapter.borrow_mut().snapshot(event);
+        for handler in handlers {
+            handler.borrow_mut().on_event_with(event, context);
         }
     }
+
+    fn snapshot(&mut self, event: &EventType) -> Vec<HandlerType<EventType, Context>> {
+        self.handlers
+            .retain(|(handler, _)| handler.strong_count() > 0);
+        self.handlers
+            .iter()
+            .filter(|(_, predicate)| predicate.as_ref().is_none_or(|predicate| predicate(event)))
+            .filter_map(|(handler, _)| handler.upgrade())
+            .collect()
+    }
 }
 
-type EventCallbackType<S> = VecDeque<Box<dyn FnMut(&Rc<S>)>>;
+type EventCallbackType<S> = VecDeque<(Option<EventToken>, Box<dyn FnMut(&Rc<S>)>)>;
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub struct EventToken(u64);
+
+type ReplayType<S> = Box<dyn Fn(&Rc<S>)>;
+
+/// Events recorded by `EventDispatcher::start_recording`, in push order.
+pub struct EventLog<EventAdapters, Context = ()>
+where
+    EventAdapters: Default,
+{
+    events: Vec<ReplayType<EventDispatcher<EventAdapters, Context>>>,
+}
 
-pub struct EventDispatcher<EventAdapters>
+impl<EventAdapters, Context> EventLog<EventAdapters, Context>
 where
     EventAdapters: Default,
 {
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+pub struct EventDispatcher<EventAdapters, Context = ()>
+where
+    EventAdapters: Default,
+{
+    id: u64,
+    next_token: Cell<u64>,
     pendings: RefCell<EventCallbackType<Self>>,
     adapters: EventAdapters,
+    context: Context,
+    recorders: RefCell<HashMap<TypeId, Box<dyn Any>>>,
+    recording: Rc<RefCell<Vec<ReplayType<Self>>>>,
 }
 
-impl<EventAdapters> EventDispatcher<EventAdapters>
+static NEXT_DISPATCHER_ID: AtomicU64 = AtomicU64::new(1);
+
+impl<EventAdapters, Context> EventDispatcher<EventAdapters, Context>
 where
     EventAdapters: Default,
+    Context: 'static,
 {
-    pub fn new() -> std::rc::Rc<Self> {
+    pub fn new() -> std::rc::Rc<Self>
+    where
+        Context: Default,
+    {
+        Self::with_context(Default::default())
+    }
+
+    ///
+    /// Create a dispatcher passing `context` to each `ContextEventHandler`.
+    ///
+    /// The dispatcher owns the context rather than taking it in `dispatch`, since events are
+    /// also dispatched by `SystemManager::update` which knows nothing about it. A context
+    /// shared with the rest of the application (e.g. `Rc<RefCell<EntityManager>>`) is then
+    /// stored once here instead of in every handler.
+    pub fn with_context(context: Context) -> std::rc::Rc<Self> {
         std::rc::Rc::new(Self {
+            id: NEXT_DISPATCHER_ID.fetch_add(1, Ordering::Relaxed),
+            next_token: Cell::new(0),
             pendings: RefCell::new(EventCallbackType::new()),
             adapters: Default::default(),
+            context,
+            recorders: RefCell::new(HashMap::new()),
+            recording: Rc::new(RefCell::new(Vec::new())),
         })
     }
 
+    /// Unique per dispatcher, a `Connection` always targets the dispatcher it was created from.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
     pub fn create_connection<EventHandlerType, EventType>(
         self: &Rc<Self>,
         handler: &Rc<RefCell<EventHandlerType>>,
     ) -> Connection<Self, EventAdapters, EventHandlerType, EventType>
     where
-        EventHandlerType: EventHandler<EventType>,
-        EventAdapters: AccessEventAdapter<EventType>,
+        EventHandlerType: ContextEventHandler<EventType, Context>,
+        EventAdapters: AccessEventAdapter<EventType, Context>,
         EventType: 'static,
     {
         Connection::new(self, handler)
     }
 
-    pub fn push<EventType>(self: &Rc<Self>, event: EventType)
+    pub fn connect_fn<EventType, F>(
+        self: &Rc<Self>,
+        f: F,
+    ) -> Connection<Self, EventAdapters, FnEventHandler<EventType>, EventType>
+    where
+        F: FnMut(&EventType) + 'static,
+        FnEventHandler<EventType>: ContextEventHandler<EventType, Context>,
+        EventAdapters: AccessEventAdapter<EventType, Context>,
+        EventType: 'static,
+    {
+        let handler = Rc::new(RefCell::new(FnEventHandler::new(f)));
+        let connection = Connection::new(self, &handler);
+        self.pendings.borrow_mut().push_back((
+            None,
+            Box::new(move |dispatch| {
+                let adapter =
+                    (&dispatch.adapters as &dyn AccessEventAdapter<EventType, Context>).get();
+                adapter.borrow_mut().connect(handler.clone());
+            }),
+        ));
+        connection
+    }
+
+    /// Connect `handler` invoked only for events for which `predicate` returns true.
+    ///
+    /// The predicate is dropped by `Connection::disconnect`, a later `Connection::connect`
+    /// connects the handler without filter.
+    pub fn connect_filtered<EventHandlerType, EventType, P>(
+        self: &Rc<Self>,
+        handler: &Rc<RefCell<EventHandlerType>>,
+        predicate: P,
+    ) -> Connection<Self, EventAdapters, EventHandlerType, EventType>
+    where
+        EventHandlerType: ContextEventHandler<EventType, Context> + 'static,
+        EventAdapters: AccessEventAdapter<EventType, Context>,
+        EventType: 'static,
+        P: Fn(&EventType) -> bool + 'static,
+    {
+        let connection = Connection::new(self, handler);
+        let handler = handler.clone();
+        let predicate: PredicateType<EventType> = Rc::new(predicate);
+        self.pendings.borrow_mut().push_back((
+            None,
+            Box::new(move |dispatch| {
+                let adapter =
+                    (&dispatch.adapters as &dyn AccessEventAdapter<EventType, Context>).get();
+                adapter
+                    .borrow_mut()
+                    .connect_filtered(handler.clone(), predicate.clone());
+            }),
+        ));
+        connection
+    }
+
+    pub fn push<EventType>(self: &Rc<Self>, event: EventType) -> EventToken
+    where
+        EventAdapters: AccessEventAdapter<EventType, Context>,
+        EventType: 'static,
+    {
+        if let Some(recorder) = self.recorders.borrow().get(&TypeId::of::<EventType>()) {
+            if let Some(recorder) = recorder.downcast_ref::<Box<dyn Fn(&EventType)>>() {
+                recorder(&event);
+            }
+        }
+        let token = EventToken(self.next_token.get());
+        self.next_token.set(token.0 + 1);
+        self.pendings.borrow_mut().push_back((
+            Some(token),
+            Box::new(move |dispatch| {
+                let adapter =
+                    (&dispatch.adapters as &dyn AccessEventAdapter<EventType, Context>).get();
+                Adapter::invoke_shared(adapter, &event, &dispatch.context);
+            }),
+        ));
+        token
+    }
+
+    /// Record (a clone of) each pushed event of this type, cf `take_recording`.
+    pub fn start_recording<EventType>(&self)
+    where
+        EventAdapters: AccessEventAdapter<EventType, Context> + 'static,
+        EventType: Clone + 'static,
+    {
+        let recording = Rc::clone(&self.recording);
+        let recorder: Box<dyn Fn(&EventType)> = Box::new(move |event: &EventType| {
+            let event = event.clone();
+            recording
+                .borrow_mut()
+                .push(Box::new(move |dispatcher: &Rc<Self>| {
+                    dispatcher.push(event.clone());
+                }));
+        });
+        self.recorders
+            .borrow_mut()
+            .insert(TypeId::of::<EventType>(), Box::new(recorder));
+    }
+
+    /// Stop recording events of this type, already recorded events are kept.
+    pub fn stop_recording<EventType>(&self)
     where
-        EventAdapters: AccessEventAdapter<EventType>,
         EventType: 'static,
     {
-        self.pendings
+        self.recorders
             .borrow_mut()
-            .push_back(Box::new(move |dispatch| {
-                let adapter = (&dispatch.adapters as &dyn AccessEventAdapter<EventType>).get();
-                adapter.borrow_mut().invoke(&event);
-            }));
+            .remove(&TypeId::of::<EventType>());
+    }
+
+    /// Take recorded events, the recording goes on with an empty log.
+    pub fn take_recording(&self) -> EventLog<EventAdapters, Context> {
+        EventLog {
+            events: std::mem::take(&mut *self.recording.borrow_mut()),
+        }
+    }
+
+    /// Push again recorded events (possibly into another dispatcher), in the same order.
+    pub fn replay(self: &Rc<Self>, log: &EventLog<EventAdapters, Context>) {
+        for event in log.events.iter() {
+            (event)(self);
+        }
+    }
+
+    /// Cancel a pending event, it works only before `dispatch` reaches the event.
+    /// Return false if the event is already dispatched or canceled.
+    pub fn cancel(&self, token: EventToken) -> bool {
+        let mut pendings = self.pendings.borrow_mut();
+        match pendings.iter().position(|(x, _)| *x == Some(token)) {
+            Some(pos) => pendings.remove(pos).is_some(),
+            None => false,
+        }
+    }
+
+    /// Cancel the last pushed pending event, cf `cancel`.
+    pub fn cancel_last(&self) -> bool {
+        let mut pendings = self.pendings.borrow_mut();
+        match pendings.iter().rposition(|(x, _)| x.is_some()) {
+            Some(pos) => pendings.remove(pos).is_some(),
+            None => false,
+        }
+    }
+
+    pub fn disconnect_all<EventType>(&self)
+    where
+        EventAdapters: AccessEventAdapter<EventType, Context>,
+        EventType: 'static,
+    {
+        self.pendings.borrow_mut().push_back((
+            None,
+            Box::new(move |dispatch| {
+                let adapter =
+                    (&dispatch.adapters as &dyn AccessEventAdapter<EventType, Context>).get();
+                adapter.borrow_mut().clear();
+            }),
+        ));
+    }
+
+    ///
+    /// Check whether `handler` is connected for `EventType`, it can be called from a handler
+    /// (even of the same event type).
+    ///
+    /// Pending connects / disconnects are not taken into account until dispatched.
+    pub fn is_connected<EventHandlerType, EventType>(
+        &self,
+        handler: &Rc<RefCell<EventHandlerType>>,
+    ) -> bool
+    where
+        EventHandlerType: ContextEventHandler<EventType, Context> + 'static,
+        EventAdapters: AccessEventAdapter<EventType, Context>,
+        EventType: 'static,
+    {
+        let adapter = (&self.adapters as &dyn AccessEventAdapter<EventType, Context>).get();
+        let handler: HandlerType<EventType, Context> = handler.clone();
+        adapter.borrow().contains(&handler)
     }
 
     pub fn dispatch(self: &Rc<Self>) {
@@ -183,54 +550,116 @@ where
         }
     }
 
+    ///
+    /// Run only the first pending event (or connect / disconnect), `false` if there is none.
+    pub fn dispatch_one(self: &Rc<Self>) -> bool {
+        match self.pop_event_() {
+            Some(mut event) => {
+                (event)(self);
+                true
+            }
+            None => false,
+        }
+    }
+
+    ///
+    /// Run at most `max` pending events (or connects / disconnects), in push order.
+    ///
+    /// Events pushed meanwhile are queued after the remaining ones, cf `pending_count`.
+    ///
+    /// # Return
+    ///
+    /// The number of events run.
+    pub fn dispatch_up_to(self: &Rc<Self>, max: usize) -> usize {
+        let mut count = 0;
+        while count < max && self.dispatch_one() {
+            count += 1;
+        }
+        count
+    }
+
+    ///
+    /// Get the number of pending events (including connects / disconnects).
+    pub fn pending_count(&self) -> usize {
+        self.pendings.borrow().len()
+    }
+
     fn pop_event_(&self) -> Option<Box<dyn FnMut(&Rc<Self>)>> {
         let mut events = self.pendings.borrow_mut();
-        events.pop_front()
+        events.pop_front().map(|(_, event)| event)
     }
 }
 
-impl<EventAdapters> Dispatcher<EventAdapters> for EventDispatcher<EventAdapters>
+impl<EventAdapters, Context> Dispatcher<EventAdapters> for EventDispatcher<EventAdapters, Context>
 where
     EventAdapters: Default,
+    Context: 'static,
 {
+    type Context = Context;
+
     fn connect<EventHandlerType, EventType>(self: &Rc<Self>, handler: Rc<RefCell<EventHandlerType>>)
     where
-        EventHandlerType: EventHandler<EventType> + 'static,
-        EventAdapters: AccessEventAdapter<EventType>,
+        EventHandlerType: ContextEventHandler<EventType, Context> + 'static,
+        EventAdapters: AccessEventAdapter<EventType, Context>,
         EventType: 'static,
     {
-        self.pendings
-            .borrow_mut()
-            .push_back(Box::new(move |dispatch| {
-                let adapter = (&dispatch.adapters as &dyn AccessEventAdapter<EventType>).get();
+        self.pendings.borrow_mut().push_back((
+            None,
+            Box::new(move |dispatch| {
+                let adapter =
+                    (&dispatch.adapters as &dyn AccessEventAdapter<EventType, Context>).get();
                 adapter.borrow_mut().connect(handler.clone());
-            }));
+            }),
+        ));
+    }
+
+    fn connect_weak<EventHandlerType, EventType>(
+        self: &Rc<Self>,
+        handler: Rc<RefCell<EventHandlerType>>,
+    ) where
+        EventHandlerType: ContextEventHandler<EventType, Context> + 'static,
+        EventAdapters: AccessEventAdapter<EventType, Context>,
+        EventType: 'static,
+    {
+        self.pendings.borrow_mut().push_back((
+            None,
+            Box::new(move |dispatch| {
+                let adapter =
+                    (&dispatch.adapters as &dyn AccessEventAdapter<EventType, Context>).get();
+                adapter.borrow_mut().connect_weak(handler.clone());
+            }),
+        ));
     }
 
     fn disconnect<EventHandlerType, EventType>(
         self: &Rc<Self>,
         handler: Rc<RefCell<EventHandlerType>>,
     ) where
-        EventHandlerType: EventHandler<EventType> + 'static,
-        EventAdapters: AccessEventAdapter<EventType>,
+        EventHandlerType: ContextEventHandler<EventType, Context> + 'static,
+        EventAdapters: AccessEventAdapter<EventType, Context>,
         EventType: 'static,
     {
-        self.pendings
-            .borrow_mut()
-            .push_back(Box::new(move |dispatch| {
-                let adapter = (&dispatch.adapters as &dyn AccessEventAdapter<EventType>).get();
+        self.pendings.borrow_mut().push_back((
+            None,
+            Box::new(move |dispatch| {
+                let adapter =
+                    (&dispatch.adapters as &dyn AccessEventAdapter<EventType, Context>).get();
                 adapter.borrow_mut().disconnect(handler.clone());
-            }));
+            }),
+        ));
     }
 }
 
 #[macro_export]
 macro_rules! create_event_adapters {
     ($name:ident { $($event:ident),* }) => {
+        entity_system::create_event_adapters!($name : () { $($event),* });
+    };
+    ($name:ident : $context:ty { $($event:ident),* }) => {
         paste::paste! {
             pub struct $name {
                 $(
-                [<adp $event:snake>] : std::cell::RefCell<entity_system::Adapter<$event>>,
+                [<adp $event:snake>] : std::cell::RefCell<entity_system::Adapter<$event, $context>>,
                 )*
             }
 
@@ -245,8 +674,8 @@ macro_rules! create_event_adapters {
             }
 
             $(
-            impl entity_system::AccessEventAdapter<$event> for $name {
-                fn get(&self) -> &std::cell::RefCell<entity_system::Adapter<$event>> {
+            impl entity_system::AccessEventAdapter<$event, $context> for $name {
+                fn get(&self) -> &std::cell::RefCell<entity_system::Adapter<$event, $context>> {
                     &self.[<adp $event:snake>]
                 }
             }
@@ -260,3 +689,22 @@ macro_rules! create_event_adapters {
         }
     };
 }
+
+///
+/// Create and connect a `Connection` per event type, for the same handler.
+///
+/// Return a tuple of connections, in the order of event types.
+#[macro_export]
+macro_rules! connect_all {
+    ($dispatcher:expr, $handler:expr, [$($event:ty),+ $(,)?]) => {{
+        let dispatcher = &$dispatcher;
+        let handler = &$handler;
+        ($(
+            {
+                let connection = dispatcher.create_connection::<_, $event>(handler);
+                connection.connect();
+                connection
+            },
+        )+)
+    }};
+}
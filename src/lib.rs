@@ -1,14 +1,22 @@
 //! Entity System Composant in Rust
 //!
 
+mod archetype;
+mod command_buffer;
 mod entity;
 mod entity_manager;
 mod event_dispatcher;
+mod resources;
 mod storage;
 mod system_manager;
+pub mod systems;
+mod world_diff;
 
+pub use command_buffer::*;
 pub use entity::*;
 pub use entity_manager::*;
 pub use event_dispatcher::*;
+pub use resources::*;
 pub use storage::*;
 pub use system_manager::*;
+pub use world_diff::*;
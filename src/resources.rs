@@ -0,0 +1,101 @@
+use std::any::{Any, TypeId};
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::HashMap;
+
+///
+/// Shared state (not attached to an entity), one value by type.
+///
+/// Owned by [`crate::SystemManager`] and given to [`crate::System::run`].
+///
+/// # Example
+/// ```rust
+/// use entity_system::Resources;
+///
+/// struct DeltaTime(f32);
+///
+/// let mut resources = Resources::new();
+/// resources.insert(DeltaTime(0.02));
+///
+/// resources.get_mut::<DeltaTime>().unwrap().0 = 0.03;
+/// assert_eq!(resources.get::<DeltaTime>().unwrap().0, 0.03);
+/// assert!(resources.get::<u32>().is_none());
+/// ```
+#[derive(Default)]
+pub struct Resources {
+    resources: HashMap<TypeId, RefCell<Box<dyn Any>>>,
+}
+
+impl Resources {
+    ///
+    /// Create an empty `Resources`
+    pub fn new() -> Self {
+        Self {
+            resources: HashMap::new(),
+        }
+    }
+
+    ///
+    /// Insert a resource, replacing the previous one of the same type
+    pub fn insert<R>(&mut self, resource: R)
+    where
+        R: 'static,
+    {
+        self.resources
+            .insert(TypeId::of::<R>(), RefCell::new(Box::new(resource)));
+    }
+
+    ///
+    /// Remove a resource
+    pub fn remove<R>(&mut self) -> Option<R>
+    where
+        R: 'static,
+    {
+        self.resources
+            .remove(&TypeId::of::<R>())
+            .and_then(|resource| resource.into_inner().downcast().ok())
+            .map(|resource| *resource)
+    }
+
+    ///
+    /// Check if a resource exists
+    pub fn contains<R>(&self) -> bool
+    where
+        R: 'static,
+    {
+        self.resources.contains_key(&TypeId::of::<R>())
+    }
+
+    ///
+    /// Get a resource, `None` if absent
+    ///
+    /// # Panics
+    ///
+    /// If the resource is mutably borrowed
+    pub fn get<R>(&self) -> Option<Ref<R>>
+    where
+        R: 'static,
+    {
+        self.resources.get(&TypeId::of::<R>()).map(|resource| {
+            Ref::map(resource.borrow(), |resource| {
+                resource.downcast_ref::<R>().unwrap()
+            })
+        })
+    }
+
+    ///
+    /// Get a resource (mutable version), `None` if absent
+    ///
+    /// # Panics
+    ///
+    /// If the resource is already borrowed
+    pub fn get_mut<R>(&self) -> Option<RefMut<R>>
+    where
+        R: 'static,
+    {
+        self.resources.get(&TypeId::of::<R>()).map(|resource| {
+            RefMut::map(resource.borrow_mut(), |resource| {
+                resource.downcast_mut::<R>().unwrap()
+            })
+        })
+    }
+}
@@ -1,4 +1,13 @@
 use crate::entity::Entity;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "profiling")]
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashSet, TryReserveError};
+use std::error::Error;
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
 
 ///
 /// Trait must be implemented to store [`crate::Component`]
@@ -11,10 +20,31 @@ pub trait Storage<T> {
     /// If allocation failed
     fn alloc(&mut self, entity: Entity);
 
+    ///
+    /// Allocation an item in the storage, without panic if memory allocation failed
+    fn try_alloc(&mut self, entity: Entity) -> Result<(), StorageError> {
+        self.alloc(entity);
+        Ok(())
+    }
+
+    ///
+    /// Reserve room for at least `additional` more items, it is only a hint (ignored by default).
+    fn reserve(&mut self, _additional: usize) {}
+
     ///
     /// Free the item in the storage
     fn free(&mut self, entity: Entity);
 
+    ///
+    /// Free all items in the storage
+    fn clear(&mut self) {
+        let mut entities = Vec::new();
+        self.for_each(&mut |entity, _| entities.push(entity));
+        for entity in entities {
+            self.free(entity);
+        }
+    }
+
     ///
     /// Get item from storage
     ///
@@ -31,9 +61,134 @@ pub trait Storage<T> {
     /// If no allocation has be done before
     fn get_mut(&mut self, entity: Entity) -> &mut T;
 
+    ///
+    /// Get item from storage, `None` if no allocation has be done before
+    fn try_get(&self, entity: Entity) -> Option<&T> {
+        if self.has(entity) {
+            Some(self.get(entity))
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Get items of several entities, `None` for entities without allocation
+    fn get_many<'a>(&'a self, entities: &[Entity]) -> Vec<Option<&'a T>> {
+        entities
+            .iter()
+            .map(|entity| self.try_get(*entity))
+            .collect()
+    }
+
+    ///
+    /// Get item from storage (mutable version), `None` if no allocation has be done before
+    fn try_get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        if self.has(entity) {
+            Some(self.get_mut(entity))
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Swap items of two entities
+    ///
+    /// # Panics
+    ///
+    /// If no allocation has be done before for one of the entities
+    fn swap(&mut self, a: Entity, b: Entity);
+
     ///
     /// Check if allocatio has been done    
     fn has(&self, entity: Entity) -> bool;
+
+    ///
+    /// Get the number of allocated items
+    ///
+    /// Should be cheap (O(1)), it is used by [`crate::EntityManager::stats`]
+    fn len(&self) -> usize;
+
+    ///
+    /// Check if no item is allocated
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///
+    /// Call f on each allocated item
+    fn for_each(&self, f: &mut dyn FnMut(Entity, &T));
+
+    ///
+    /// Call f on each allocated item (mutable version)
+    fn for_each_mut(&mut self, f: &mut dyn FnMut(Entity, &mut T));
+
+    ///
+    /// Release unused memory (e.g. slots after the last allocated item).
+    fn shrink_to_fit(&mut self) {}
+
+    ///
+    /// Reset access statistics.
+    ///
+    /// Only used by storage with access statistics (`profiling` feature).
+    fn reset_access_stats(&mut self) {}
+
+    ///
+    /// Update the current tick.
+    ///
+    /// Only used by storage with change tracking (cf [`TrackedStorage`]).
+    fn set_tick(&mut self, _tick: u64) {}
+}
+
+fn swap_panic<T>(a: Entity, b: Entity) -> ! {
+    panic!(
+        "cannot swap {} of entities {} and {}: not allocated",
+        std::any::type_name::<T>(),
+        a.id,
+        b.id
+    )
+}
+
+///
+/// Error returned by [`Storage::try_alloc`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StorageError {
+    ///
+    /// Not enough memory to allocate the item of the `Entity`.
+    OutOfMemory(Entity),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageError::OutOfMemory(entity) => {
+                write!(f, "not enough memory to allocate entity {}", entity.id)
+            }
+        }
+    }
+}
+
+impl Error for StorageError {}
+
+///
+/// Trait must be implemented by storage with change tracking.
+pub trait TrackedStorage<T>: Storage<T> {
+    ///
+    /// Check if the item has been allocated or mutably accessed since `tick` (included).
+    fn changed_since(&self, entity: Entity, tick: u64) -> bool;
+}
+
+///
+/// Trait must be implemented by storage supporting parallel iteration.
+///
+/// Only available with the `rayon` feature.
+#[cfg(feature = "rayon")]
+pub trait ParStorage<T>: Storage<T>
+where
+    T: Send,
+{
+    ///
+    /// Call f on each allocated item, items are split across threads
+    fn par_for_each_mut(&mut self, f: &(dyn Fn(Entity, &mut T) + Sync));
 }
 
 ///
@@ -59,6 +214,9 @@ pub trait Storage<T> {
 ///
 ///     // free
 ///     storage.free(entity);
+///
+///     // read the value without panic
+///     assert!(storage.try_get(entity).is_none());
 /// ```
 #[derive(Default)]
 pub struct BasicVecStorage<T>
@@ -66,7 +224,145 @@ where
     T: Default,
 {
     datas: Vec<T>,
-    alloc: Vec<bool>,
+    alloc: AllocBits,
+    len: usize,
+    #[cfg(feature = "profiling")]
+    stats: Cell<AccessStats>,
+}
+
+///
+/// Allocation flags of a [`BasicVecStorage`], one bit per item.
+#[derive(Default)]
+struct AllocBits {
+    words: Vec<u64>,
+}
+
+impl AllocBits {
+    const BITS: usize = u64::BITS as usize;
+
+    fn contains(&self, pos: usize) -> bool {
+        self.words
+            .get(pos / Self::BITS)
+            .is_some_and(|word| word & (1 << (pos % Self::BITS)) != 0)
+    }
+
+    fn insert(&mut self, pos: usize) -> bool {
+        let index = pos / Self::BITS;
+        if index >= self.words.len() {
+            self.words.resize(index + 1, 0);
+        }
+        let mask = 1 << (pos % Self::BITS);
+        let inserted = self.words[index] & mask == 0;
+        self.words[index] |= mask;
+        inserted
+    }
+
+    fn remove(&mut self, pos: usize) -> bool {
+        match self.words.get_mut(pos / Self::BITS) {
+            Some(word) => {
+                let mask = 1 << (pos % Self::BITS);
+                let removed = *word & mask != 0;
+                *word &= !mask;
+                removed
+            }
+            None => false,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.words.clear();
+    }
+
+    fn reserve(&mut self, len: usize) {
+        let words = len.div_ceil(Self::BITS);
+        self.words.reserve(words.saturating_sub(self.words.len()));
+    }
+
+    fn try_reserve(&mut self, len: usize) -> Result<(), TryReserveError> {
+        let words = len.div_ceil(Self::BITS);
+        self.words
+            .try_reserve(words.saturating_sub(self.words.len()))
+    }
+
+    ///
+    /// Position after the last set bit
+    fn end(&self) -> usize {
+        self.words
+            .iter()
+            .rposition(|word| *word != 0)
+            .map_or(0, |index| {
+                index * Self::BITS + Self::BITS - self.words[index].leading_zeros() as usize
+            })
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.words.truncate(len.div_ceil(Self::BITS));
+        if let Some(last) = self.words.last_mut() {
+            let used = len % Self::BITS;
+            if used != 0 {
+                *last &= (1 << used) - 1;
+            }
+        }
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.words.shrink_to_fit();
+    }
+
+    ///
+    /// Iterate over set bits, 64 at a time
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    None
+                } else {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    Some(index * Self::BITS + bit)
+                }
+            })
+        })
+    }
+}
+
+///
+/// Access statistics of a [`BasicVecStorage`].
+///
+/// Only available with the `profiling` feature.
+#[cfg(feature = "profiling")]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct AccessStats {
+    /// number of `get` / `try_get` calls
+    pub get: u64,
+    /// number of `get_mut` / `try_get_mut` calls
+    pub get_mut: u64,
+    /// number of `alloc` calls
+    pub alloc: u64,
+    /// number of `free` calls
+    pub free: u64,
+}
+
+#[cfg(feature = "profiling")]
+impl<T> BasicVecStorage<T>
+where
+    T: Default,
+{
+    ///
+    /// Get access statistics since creation or last reset
+    pub fn access_stats(&self) -> AccessStats {
+        self.stats.get()
+    }
+
+    fn record<F>(&self, f: F)
+    where
+        F: FnOnce(&mut AccessStats),
+    {
+        let mut stats = self.stats.get();
+        f(&mut stats);
+        self.stats.set(stats);
+    }
 }
 
 impl<T> Storage<T> for BasicVecStorage<T>
@@ -74,25 +370,58 @@ where
     T: Default,
 {
     fn alloc(&mut self, entity: Entity) {
+        #[cfg(feature = "profiling")]
+        self.record(|stats| stats.alloc += 1);
         let pos = entity.id as usize;
         if pos >= self.datas.len() {
             self.datas.resize_with(pos + 1, Default::default);
-            self.alloc.resize_with(pos + 1, Default::default);
         }
-        self.alloc[pos] = true;
+        if self.alloc.insert(pos) {
+            self.len += 1;
+        }
+    }
+
+    fn try_alloc(&mut self, entity: Entity) -> Result<(), StorageError> {
+        let pos = entity.id as usize;
+        if pos >= self.datas.len() {
+            let additional = pos + 1 - self.datas.len();
+            self.datas
+                .try_reserve(additional)
+                .and_then(|_| self.alloc.try_reserve(pos + 1))
+                .map_err(|_| StorageError::OutOfMemory(entity))?;
+        }
+        self.alloc(entity);
+        Ok(())
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.datas.reserve(additional);
+        self.alloc.reserve(self.datas.len() + additional);
     }
 
     fn free(&mut self, entity: Entity) {
+        #[cfg(feature = "profiling")]
+        self.record(|stats| stats.free += 1);
         let pos = entity.id as usize;
-        if pos < self.datas.len() && self.alloc[pos] {
+        if self.alloc.remove(pos) {
             self.datas[pos] = Default::default();
-            self.alloc[pos] = false;
+            self.len -= 1;
         }
     }
 
+    fn clear(&mut self) {
+        #[cfg(feature = "profiling")]
+        self.record(|stats| stats.free += self.len as u64);
+        self.datas.clear();
+        self.alloc.clear();
+        self.len = 0;
+    }
+
     fn get(&self, entity: Entity) -> &T {
+        #[cfg(feature = "profiling")]
+        self.record(|stats| stats.get += 1);
         let pos = entity.id as usize;
-        if pos < self.datas.len() && self.alloc[pos] {
+        if self.alloc.contains(pos) {
             self.datas.get(pos).unwrap()
         } else {
             panic!("index is out of bounds or not allocated");
@@ -100,16 +429,497 @@ where
     }
 
     fn get_mut(&mut self, entity: Entity) -> &mut T {
+        #[cfg(feature = "profiling")]
+        self.record(|stats| stats.get_mut += 1);
         let pos = entity.id as usize;
-        if pos < self.datas.len() && self.alloc[pos] {
+        if self.alloc.contains(pos) {
             self.datas.get_mut(pos).unwrap()
         } else {
             panic!("index is out of bounds or not allocated");
         }
     }
 
+    fn try_get(&self, entity: Entity) -> Option<&T> {
+        #[cfg(feature = "profiling")]
+        self.record(|stats| stats.get += 1);
+        let pos = entity.id as usize;
+        if self.alloc.contains(pos) {
+            self.datas.get(pos)
+        } else {
+            None
+        }
+    }
+
+    fn try_get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        #[cfg(feature = "profiling")]
+        self.record(|stats| stats.get_mut += 1);
+        let pos = entity.id as usize;
+        if self.alloc.contains(pos) {
+            self.datas.get_mut(pos)
+        } else {
+            None
+        }
+    }
+
+    fn swap(&mut self, a: Entity, b: Entity) {
+        if !self.has(a) || !self.has(b) {
+            swap_panic::<T>(a, b);
+        }
+        self.datas.swap(a.id as usize, b.id as usize);
+    }
+
     fn has(&self, entity: Entity) -> bool {
         let pos = entity.id as usize;
-        pos < self.datas.len() && self.alloc[pos]
+        self.alloc.contains(pos)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn shrink_to_fit(&mut self) {
+        let len = self.alloc.end();
+        self.datas.truncate(len);
+        self.alloc.truncate(len);
+        self.datas.shrink_to_fit();
+        self.alloc.shrink_to_fit();
+    }
+
+    #[cfg(feature = "profiling")]
+    fn reset_access_stats(&mut self) {
+        self.stats.set(Default::default());
+    }
+
+    fn for_each(&self, f: &mut dyn FnMut(Entity, &T)) {
+        for pos in self.alloc.iter() {
+            f(Entity::new(pos as u32), &self.datas[pos]);
+        }
+    }
+
+    fn for_each_mut(&mut self, f: &mut dyn FnMut(Entity, &mut T)) {
+        for pos in self.alloc.iter() {
+            f(Entity::new(pos as u32), &mut self.datas[pos]);
+        }
+    }
+}
+
+///
+/// Implementation of Storage<T> with a [`Vec`] as underlying and change tracking.
+///
+/// The tick of the last allocation or mutable access is recorded per item.
+///
+/// # Example
+/// ```rust
+///     use entity_system::{Entity, Storage, TrackedStorage, TrackedVecStorage};
+///
+///     let mut storage : TrackedVecStorage<u32> = Default::default();
+///     let entity = Entity::new(0);
+///
+///     storage.set_tick(1);
+///     storage.alloc(entity);
+///     assert!(storage.changed_since(entity, 1));
+///
+///     storage.set_tick(2);
+///     assert!(!storage.changed_since(entity, 2));
+///
+///     *storage.get_mut(entity) = 5;
+///     assert!(storage.changed_since(entity, 2));
+/// ```
+#[derive(Default)]
+pub struct TrackedVecStorage<T>
+where
+    T: Default,
+{
+    storage: BasicVecStorage<T>,
+    ticks: Vec<u64>,
+    tick: u64,
+}
+
+#[cfg(feature = "rayon")]
+impl<T> ParStorage<T> for BasicVecStorage<T>
+where
+    T: Default + Send,
+{
+    fn par_for_each_mut(&mut self, f: &(dyn Fn(Entity, &mut T) + Sync)) {
+        let alloc = &self.alloc;
+        self.datas
+            .par_iter_mut()
+            .enumerate()
+            .filter(|(pos, _)| alloc.contains(*pos))
+            .for_each(|(pos, data)| f(Entity::new(pos as u32), data));
+    }
+}
+
+impl<T> TrackedVecStorage<T>
+where
+    T: Default,
+{
+    fn touch(&mut self, entity: Entity) {
+        let pos = entity.id as usize;
+        if pos >= self.ticks.len() {
+            self.ticks.resize_with(pos + 1, Default::default);
+        }
+        self.ticks[pos] = self.tick;
+    }
+}
+
+#[cfg(feature = "profiling")]
+impl<T> TrackedVecStorage<T>
+where
+    T: Default,
+{
+    ///
+    /// Get access statistics since creation or last reset
+    pub fn access_stats(&self) -> AccessStats {
+        self.storage.access_stats()
+    }
+}
+
+impl<T> Storage<T> for TrackedVecStorage<T>
+where
+    T: Default,
+{
+    fn alloc(&mut self, entity: Entity) {
+        self.storage.alloc(entity);
+        self.touch(entity);
+    }
+
+    fn try_alloc(&mut self, entity: Entity) -> Result<(), StorageError> {
+        let pos = entity.id as usize;
+        if pos >= self.ticks.len() {
+            self.ticks
+                .try_reserve(pos + 1 - self.ticks.len())
+                .map_err(|_| StorageError::OutOfMemory(entity))?;
+        }
+        self.storage.try_alloc(entity)?;
+        self.touch(entity);
+        Ok(())
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.storage.reserve(additional);
+        self.ticks.reserve(additional);
+    }
+
+    fn free(&mut self, entity: Entity) {
+        self.storage.free(entity);
+    }
+
+    fn clear(&mut self) {
+        self.storage.clear();
+    }
+
+    fn get(&self, entity: Entity) -> &T {
+        self.storage.get(entity)
+    }
+
+    fn try_get(&self, entity: Entity) -> Option<&T> {
+        self.storage.try_get(entity)
+    }
+
+    fn get_mut(&mut self, entity: Entity) -> &mut T {
+        if self.storage.has(entity) {
+            self.touch(entity);
+        }
+        self.storage.get_mut(entity)
+    }
+
+    fn swap(&mut self, a: Entity, b: Entity) {
+        self.storage.swap(a, b);
+        self.touch(a);
+        self.touch(b);
+    }
+
+    fn has(&self, entity: Entity) -> bool {
+        self.storage.has(entity)
+    }
+
+    fn len(&self) -> usize {
+        self.storage.len()
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.storage.shrink_to_fit();
+        self.ticks.truncate(self.storage.datas.len());
+        self.ticks.shrink_to_fit();
+    }
+
+    fn for_each(&self, f: &mut dyn FnMut(Entity, &T)) {
+        self.storage.for_each(f);
+    }
+
+    fn for_each_mut(&mut self, f: &mut dyn FnMut(Entity, &mut T)) {
+        let tick = self.tick;
+        let ticks = &mut self.ticks;
+        self.storage.for_each_mut(&mut |entity, value| {
+            ticks[entity.id as usize] = tick;
+            f(entity, value);
+        });
+    }
+
+    fn reset_access_stats(&mut self) {
+        self.storage.reset_access_stats();
+    }
+
+    fn set_tick(&mut self, tick: u64) {
+        self.tick = tick;
+    }
+}
+
+impl<T> TrackedStorage<T> for TrackedVecStorage<T>
+where
+    T: Default,
+{
+    fn changed_since(&self, entity: Entity, tick: u64) -> bool {
+        self.storage.has(entity) && self.ticks[entity.id as usize] >= tick
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T> ParStorage<T> for TrackedVecStorage<T>
+where
+    T: Default + Send,
+{
+    fn par_for_each_mut(&mut self, f: &(dyn Fn(Entity, &mut T) + Sync)) {
+        for pos in self.storage.alloc.iter() {
+            self.ticks[pos] = self.tick;
+        }
+        self.storage.par_for_each_mut(f);
+    }
+}
+
+///
+/// Trait must be implemented by items of [`SortedStorage`], to give their sort key.
+pub trait SortKey<K>
+where
+    K: Ord,
+{
+    ///
+    /// Get the sort key
+    fn key(&self) -> K;
+}
+
+///
+/// Implementation of Storage<T> with a [`Vec`] as underlying and an index sorted by key.
+///
+/// Keys of mutably accessed items are re-computed by the next [`SortedStorage::range`] call.
+///
+/// # Example
+/// ```rust
+///     use entity_system::{Entity, Storage, SortKey, SortedStorage};
+///
+///     #[derive(Default)]
+///     struct Position {
+///         x: i32,
+///     }
+///
+///     impl SortKey<i32> for Position {
+///         fn key(&self) -> i32 {
+///             self.x
+///         }
+///     }
+///
+///     let mut storage : SortedStorage<Position, i32> = Default::default();
+///     for id in 0..4 {
+///         storage.alloc(Entity::new(id));
+///         storage.get_mut(Entity::new(id)).x = 10 - id as i32;
+///     }
+///
+///     let entities: Vec<_> = storage.range(8..=9).map(|(entity, _)| entity.id).collect();
+///     assert_eq!(entities, [2, 1]);
+/// ```
+pub struct SortedStorage<T, K>
+where
+    T: Default + SortKey<K>,
+    K: Ord + Clone,
+{
+    storage: BasicVecStorage<T>,
+    index: RefCell<SortedIndex<K>>,
+}
+
+struct SortedIndex<K> {
+    sorted: BTreeSet<(K, Entity)>,
+    keys: Vec<Option<K>>,
+    dirty: HashSet<Entity>,
+    all_dirty: bool,
+}
+
+impl<K> SortedIndex<K>
+where
+    K: Ord + Clone,
+{
+    fn set(&mut self, entity: Entity, key: Option<K>) {
+        let pos = entity.id as usize;
+        if pos >= self.keys.len() {
+            self.keys.resize_with(pos + 1, Default::default);
+        }
+        if let Some(old) = self.keys[pos].take() {
+            self.sorted.remove(&(old, entity));
+        }
+        if let Some(key) = key {
+            self.sorted.insert((key.clone(), entity));
+            self.keys[pos] = Some(key);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.sorted.clear();
+        self.keys.clear();
+        self.dirty.clear();
+        self.all_dirty = false;
+    }
+}
+
+impl<T, K> Default for SortedStorage<T, K>
+where
+    T: Default + SortKey<K>,
+    K: Ord + Clone,
+{
+    fn default() -> Self {
+        Self {
+            storage: Default::default(),
+            index: RefCell::new(SortedIndex {
+                sorted: BTreeSet::new(),
+                keys: Vec::new(),
+                dirty: HashSet::new(),
+                all_dirty: false,
+            }),
+        }
+    }
+}
+
+impl<T, K> SortedStorage<T, K>
+where
+    T: Default + SortKey<K>,
+    K: Ord + Clone,
+{
+    ///
+    /// Iterate over items with a key in range, sorted by key (then by `Entity`)
+    pub fn range<R>(&self, range: R) -> impl Iterator<Item = (Entity, &T)> + '_
+    where
+        R: RangeBounds<K>,
+    {
+        self.refresh();
+        let start = match range.start_bound() {
+            Bound::Included(key) => Bound::Included((key.clone(), Entity::new(0))),
+            Bound::Excluded(key) => Bound::Excluded((key.clone(), Entity::INVALID)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(key) => Bound::Included((key.clone(), Entity::INVALID)),
+            Bound::Excluded(key) => Bound::Excluded((key.clone(), Entity::new(0))),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let entities: Vec<Entity> = self
+            .index
+            .borrow()
+            .sorted
+            .range((start, end))
+            .map(|(_, entity)| *entity)
+            .collect();
+        entities
+            .into_iter()
+            .map(move |entity| (entity, self.storage.get(entity)))
+    }
+
+    fn refresh(&self) {
+        let mut index = self.index.borrow_mut();
+        let dirty: Vec<Entity> = if index.all_dirty {
+            index.all_dirty = false;
+            index.dirty.clear();
+            let mut entities = Vec::with_capacity(self.storage.len());
+            self.storage
+                .for_each(&mut |entity, _| entities.push(entity));
+            entities
+        } else {
+            index.dirty.drain().collect()
+        };
+        for entity in dirty {
+            let key = self.storage.try_get(entity).map(|item| item.key());
+            index.set(entity, key);
+        }
+    }
+}
+
+impl<T, K> Storage<T> for SortedStorage<T, K>
+where
+    T: Default + SortKey<K>,
+    K: Ord + Clone,
+{
+    fn alloc(&mut self, entity: Entity) {
+        self.storage.alloc(entity);
+        self.index.get_mut().dirty.insert(entity);
+    }
+
+    fn try_alloc(&mut self, entity: Entity) -> Result<(), StorageError> {
+        self.storage.try_alloc(entity)?;
+        self.index.get_mut().dirty.insert(entity);
+        Ok(())
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.storage.reserve(additional);
+    }
+
+    fn free(&mut self, entity: Entity) {
+        self.storage.free(entity);
+        let index = self.index.get_mut();
+        index.dirty.remove(&entity);
+        index.set(entity, None);
+    }
+
+    fn clear(&mut self) {
+        self.storage.clear();
+        self.index.get_mut().clear();
+    }
+
+    fn get(&self, entity: Entity) -> &T {
+        self.storage.get(entity)
+    }
+
+    fn try_get(&self, entity: Entity) -> Option<&T> {
+        self.storage.try_get(entity)
+    }
+
+    fn get_mut(&mut self, entity: Entity) -> &mut T {
+        if self.storage.has(entity) {
+            self.index.get_mut().dirty.insert(entity);
+        }
+        self.storage.get_mut(entity)
+    }
+
+    fn swap(&mut self, a: Entity, b: Entity) {
+        self.storage.swap(a, b);
+        let index = self.index.get_mut();
+        index.dirty.insert(a);
+        index.dirty.insert(b);
+    }
+
+    fn has(&self, entity: Entity) -> bool {
+        self.storage.has(entity)
+    }
+
+    fn len(&self) -> usize {
+        self.storage.len()
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.storage.shrink_to_fit();
+        let index = self.index.get_mut();
+        index.keys.truncate(self.storage.datas.len());
+        index.keys.shrink_to_fit();
+    }
+
+    fn for_each(&self, f: &mut dyn FnMut(Entity, &T)) {
+        self.storage.for_each(f);
+    }
+
+    fn for_each_mut(&mut self, f: &mut dyn FnMut(Entity, &mut T)) {
+        self.index.get_mut().all_dirty = true;
+        self.storage.for_each_mut(f);
+    }
+
+    fn reset_access_stats(&mut self) {
+        self.storage.reset_access_stats();
     }
 }
@@ -1,9 +1,14 @@
+use crate::entity_manager::EntityManagerComponent;
 use crate::event_dispatcher::EventDispatcher;
-use std::cell::RefCell;
+use crate::resources::Resources;
+use std::cell::{Cell, Ref, RefCell, RefMut};
 use std::cmp::{max, Ord, Ordering};
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
 use std::rc::Rc;
-use std::time::Instant;
+use std::thread;
+use std::time::{Duration, Instant};
 
 ///
 /// Definie the system execution period.
@@ -13,13 +18,45 @@ pub enum RefreshPeriod {
     /// Each time.
     EveryTime,
     ///
-    /// After a date.
+    /// After a date: the system runs once the date is reached.
     At(Instant),
     ///
     /// Stop to refresh.
     Stop,
 }
 
+impl RefreshPeriod {
+    ///
+    /// Refresh after a delay from now.
+    pub fn after(delay: Duration) -> Self {
+        RefreshPeriod::At(Instant::now() + delay)
+    }
+
+    ///
+    /// Refresh after a delay from now, in milliseconds.
+    pub fn in_ms(delay: u64) -> Self {
+        Self::after(Duration::from_millis(delay))
+    }
+
+    ///
+    /// Refresh after one period of the frequency `hz`, from now.
+    ///
+    /// # Panics
+    ///
+    /// If `hz` is not strictly positive.
+    pub fn in_hz(hz: f64) -> Self {
+        Self::after(Duration::from_secs_f64(1.0 / hz))
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        match self {
+            RefreshPeriod::EveryTime => true,
+            RefreshPeriod::At(time) => *time <= now,
+            RefreshPeriod::Stop => false,
+        }
+    }
+}
+
 impl Ord for RefreshPeriod {
     fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
@@ -48,7 +85,7 @@ impl PartialOrd for RefreshPeriod {
 /// It will be executed by [`SystemManager`]
 /// # Example
 /// ```rust
-/// use entity_system::{System, RefreshPeriod};
+/// use entity_system::{System, RefreshPeriod, Resources, SystemResult};
 /// use std::time::Instant;
 ///
 /// struct MoveSystem {
@@ -59,12 +96,12 @@ impl PartialOrd for RefreshPeriod {
 ///         "move"
 ///     }
 ///
-///     fn run(&mut self, now : Instant) -> RefreshPeriod {
+///     fn run(&mut self, now : Instant, resources: &Resources) -> SystemResult {
 ///         //
 ///         // Do lot of thing
 ///         //
 ///
-///         RefreshPeriod::EveryTime
+///         Ok(RefreshPeriod::EveryTime)
 ///     }         
 /// }
 /// ```
@@ -80,11 +117,128 @@ pub trait System {
     ///
     /// # Arguments
     /// * `now` system rum time.
+    /// * `resources` shared resources of [`SystemManager`].
     ///
     /// # Return
     ///
-    /// The next execution time.
-    fn run(&mut self, now: Instant) -> RefreshPeriod;
+    /// The next execution time or an error.
+    fn run(&mut self, now: Instant, resources: &Resources) -> SystemResult;
+
+    ///
+    /// Called when the system is added to [`SystemManager`].
+    fn on_start(&mut self) {}
+
+    ///
+    /// Called when the system is removed from [`SystemManager`].
+    fn on_stop(&mut self) {}
+
+    ///
+    /// Get names of components needed by the system, checked by [`SystemManager::validate`].
+    fn required_components(&self) -> &'static [&'static str] {
+        &[]
+    }
+}
+
+///
+/// Result of [`System::run`].
+pub type SystemResult = Result<RefreshPeriod, Box<dyn Error>>;
+
+///
+/// Error returned by a [`System`], with the name of the failing system.
+#[derive(Debug)]
+pub struct SystemError {
+    name: &'static str,
+    error: Box<dyn Error>,
+}
+
+impl SystemError {
+    ///
+    /// Create a new `SystemError`
+    pub fn new(name: &'static str, error: Box<dyn Error>) -> Self {
+        Self { name, error }
+    }
+
+    ///
+    /// Get the name of the failing system
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    ///
+    /// Get the error returned by the system
+    pub fn error(&self) -> &dyn Error {
+        self.error.as_ref()
+    }
+}
+
+impl fmt::Display for SystemError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "system {} failed: {}", self.name, self.error)
+    }
+}
+
+impl Error for SystemError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
+
+///
+/// When [`SystemManager`] dispatches pending events, cf [`SystemManager::set_dispatch_mode`].
+///
+/// Each dispatch runs pending events until none is left, including events pushed by handlers.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum DispatchMode {
+    ///
+    /// After each executed system (default).
+    #[default]
+    AfterEach,
+    ///
+    /// Once, after all systems.
+    AfterAll,
+}
+
+///
+/// Run time of a [`System`], cf [`SystemManager::timing`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct SystemTiming {
+    ///
+    /// Duration of the last run.
+    pub last: Duration,
+    ///
+    /// Exponential moving average of run durations (the last run weights 10%).
+    pub avg: Duration,
+}
+
+impl SystemTiming {
+    fn update(timing: Option<Self>, last: Duration) -> Self {
+        let avg = match timing {
+            Some(timing) => timing.avg.mul_f64(0.9) + last.mul_f64(0.1),
+            None => last,
+        };
+        Self { last, avg }
+    }
+}
+
+///
+/// Shared read access to the frame number of a [`SystemManager`].
+///
+/// Cf [`SystemManager::frame`].
+#[derive(Clone, Default)]
+pub struct FrameCounter {
+    frame: Rc<Cell<u64>>,
+}
+
+impl FrameCounter {
+    ///
+    /// Get the frame number
+    pub fn get(&self) -> u64 {
+        self.frame.get()
+    }
+
+    fn increment(&self) {
+        self.frame.set(self.frame.get() + 1);
+    }
 }
 
 ///
@@ -94,7 +248,7 @@ pub trait System {
 /// ```rust
 /// use std::cell::RefCell;
 /// use std::rc::Rc;
-/// use entity_system::{SystemManager, System, RefreshPeriod};
+/// use entity_system::{SystemManager, System, RefreshPeriod, Resources, SystemResult};
 /// use std::time::Instant;
 ///
 /// entity_system::create_event_adapters!(EventAdapters {});
@@ -108,45 +262,286 @@ pub trait System {
 ///         "move"
 ///     }
 ///
-///     fn run(&mut self, now : Instant) -> RefreshPeriod {
+///     fn run(&mut self, now : Instant, resources: &Resources) -> SystemResult {
 ///         //
 ///         // Do lot of thing
 ///         //
 ///
-///         RefreshPeriod::EveryTime
+///         Ok(RefreshPeriod::EveryTime)
 ///     }         
 /// }
 ///
 /// let event_dispatcher = EventDispatcher::new();
 /// let mut system_manager = SystemManager::new();
 /// system_manager.add_system(Rc::new(RefCell::new(MoveSystem {})));
-/// system_manager.update(&event_dispatcher);
+/// system_manager.update(&event_dispatcher).unwrap();
 /// ```
 pub struct SystemManager {
     systems: Vec<Rc<RefCell<dyn System>>>,
     refresh: Vec<RefCell<RefreshPeriod>>,
     names: HashMap<&'static str, usize>,
+    frame: FrameCounter,
+    resources: Resources,
+    timings: Vec<Cell<Option<SystemTiming>>>,
+    priorities: Vec<i32>,
+    dispatch_mode: DispatchMode,
+    stages: Vec<(String, Vec<usize>)>,
 }
 
 impl SystemManager {
+    ///
+    /// Stage of systems added by [`SystemManager::add_system`].
+    pub const DEFAULT_STAGE: &'static str = "default";
+
     pub fn new() -> Self {
         Self {
             systems: Vec::new(),
             refresh: Vec::new(),
             names: HashMap::new(),
+            frame: Default::default(),
+            resources: Resources::new(),
+            timings: Vec::new(),
+            priorities: Vec::new(),
+            dispatch_mode: DispatchMode::default(),
+            stages: Vec::new(),
         }
     }
 
     ///
-    /// Add a system
+    /// Get the frame number.
+    ///
+    /// It is incremented once per [`SystemManager::update`] call (not per system run),
+    /// before executing systems. So systems see `1` during the first update.
+    pub fn frame(&self) -> u64 {
+        self.frame.get()
+    }
+
+    ///
+    /// Set when pending events are dispatched during [`SystemManager::update`],
+    /// [`SystemManager::run_stage`] and [`SystemManager::update_with_budget`].
+    pub fn set_dispatch_mode(&mut self, mode: DispatchMode) {
+        self.dispatch_mode = mode;
+    }
+
+    ///
+    /// Get when pending events are dispatched.
+    pub fn dispatch_mode(&self) -> DispatchMode {
+        self.dispatch_mode
+    }
+
+    ///
+    /// Get a shared read access to the frame number, to be kept by systems.
+    pub fn frame_counter(&self) -> FrameCounter {
+        self.frame.clone()
+    }
+
+    ///
+    /// Get shared resources, given to [`System::run`].
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    ///
+    /// Insert a shared resource, replacing the previous one of the same type.
+    pub fn insert_resource<R>(&mut self, resource: R)
+    where
+        R: 'static,
+    {
+        self.resources.insert(resource);
+    }
+
+    ///
+    /// Get a shared resource, `None` if absent.
+    pub fn get_resource<R>(&self) -> Option<Ref<R>>
+    where
+        R: 'static,
+    {
+        self.resources.get()
+    }
+
+    ///
+    /// Get a shared resource (mutable version), `None` if absent.
+    pub fn get_resource_mut<R>(&self) -> Option<RefMut<R>>
+    where
+        R: 'static,
+    {
+        self.resources.get_mut()
+    }
+
+    ///
+    /// Add a system to [`SystemManager::DEFAULT_STAGE`], [`System::on_start`] is called
+    ///
+    /// # Panics
+    ///
+    /// If a system with the same name is already added.
     pub fn add_system<S>(&mut self, system: Rc<RefCell<S>>)
     where
         S: System + 'static,
     {
-        self.names
-            .insert(system.borrow().name(), self.systems.len());
+        self.add_to_stage(Self::DEFAULT_STAGE, system);
+    }
+
+    ///
+    /// Add a system to [`SystemManager::DEFAULT_STAGE`] with a priority, [`System::on_start`] is called
+    ///
+    /// Systems of a stage are executed by decreasing priority, then in their registration order
+    /// (systems added without priority have priority `0`).
+    /// Priority does not change stage order: a stage is always executed after previous stages.
+    ///
+    /// # Panics
+    ///
+    /// If a system with the same name is already added.
+    pub fn add_system_with_priority<S>(&mut self, system: Rc<RefCell<S>>, priority: i32)
+    where
+        S: System + 'static,
+    {
+        self.add_to_stage_with_priority(Self::DEFAULT_STAGE, system, priority);
+    }
+
+    ///
+    /// Add a system to a stage, [`System::on_start`] is called
+    ///
+    /// Stages are executed by [`SystemManager::update`] in their registration order
+    /// (first system added), systems of a stage in their registration order
+    /// (cf [`SystemManager::add_to_stage_with_priority`]).
+    ///
+    /// # Panics
+    ///
+    /// If a system with the same name is already added.
+    pub fn add_to_stage<S>(&mut self, stage: &str, system: Rc<RefCell<S>>)
+    where
+        S: System + 'static,
+    {
+        self.add_to_stage_with_priority(stage, system, 0);
+    }
+
+    ///
+    /// Add a system to a stage with a priority, [`System::on_start`] is called
+    ///
+    /// Cf [`SystemManager::add_system_with_priority`] about priority
+    /// and [`SystemManager::add_to_stage`] about stages.
+    ///
+    /// # Panics
+    ///
+    /// If a system with the same name is already added.
+    pub fn add_to_stage_with_priority<S>(
+        &mut self,
+        stage: &str,
+        system: Rc<RefCell<S>>,
+        priority: i32,
+    ) where
+        S: System + 'static,
+    {
+        let name = system.borrow().name();
+        if self.names.contains_key(name) {
+            panic!("system {} already added", name);
+        }
+        let id = self.systems.len();
+        system.borrow_mut().on_start();
+        self.names.insert(name, id);
         self.systems.push(system);
         self.refresh.push(RefCell::new(RefreshPeriod::EveryTime));
+        self.timings.push(Cell::new(None));
+        self.priorities.push(priority);
+        let priorities = &self.priorities;
+        match self.stages.iter_mut().find(|(name, _)| name == stage) {
+            Some((_, ids)) => {
+                let pos = ids
+                    .iter()
+                    .position(|pos| priorities[*pos] < priority)
+                    .unwrap_or(ids.len());
+                ids.insert(pos, id);
+            }
+            None => self.stages.push((stage.to_string(), vec![id])),
+        }
+    }
+
+    ///
+    /// Remove a system, [`System::on_stop`] is called
+    ///
+    /// # Return
+    ///
+    /// The removed system, `None` if no system has this name.
+    pub fn remove_system(&mut self, name: &str) -> Option<Rc<RefCell<dyn System>>> {
+        let id = self.names.remove(name)?;
+        let system = self.systems.remove(id);
+        self.refresh.remove(id);
+        self.timings.remove(id);
+        self.priorities.remove(id);
+        for pos in self.names.values_mut() {
+            if *pos > id {
+                *pos -= 1;
+            }
+        }
+        for (_, ids) in self.stages.iter_mut() {
+            ids.retain(|pos| *pos != id);
+            for pos in ids.iter_mut() {
+                if *pos > id {
+                    *pos -= 1;
+                }
+            }
+        }
+        self.stages.retain(|(_, ids)| !ids.is_empty());
+        system.borrow_mut().on_stop();
+        Some(system)
+    }
+
+    ///
+    /// Replace the system with the same name, keeping its refresh period,
+    /// cf [`SystemManager::replace_system_reset`] to reset it.
+    ///
+    /// [`System::on_stop`] is called on the old system and [`System::on_start`] on the new one.
+    ///
+    /// # Return
+    ///
+    /// `false` if no system has this name (the system is not added).
+    pub fn replace_system<S>(&mut self, system: Rc<RefCell<S>>) -> bool
+    where
+        S: System + 'static,
+    {
+        let id = match self.names.get(system.borrow().name()) {
+            Some(id) => *id,
+            None => return false,
+        };
+        self.systems[id].borrow_mut().on_stop();
+        system.borrow_mut().on_start();
+        self.systems[id] = system;
+        self.timings[id].set(None);
+        true
+    }
+
+    ///
+    /// Replace the system with the same name, resetting its refresh period
+    /// to [`RefreshPeriod::EveryTime`].
+    ///
+    /// Lifecycle hooks are called as in [`SystemManager::replace_system`].
+    ///
+    /// # Return
+    ///
+    /// `false` if no system has this name (the system is not added).
+    pub fn replace_system_reset<S>(&mut self, system: Rc<RefCell<S>>) -> bool
+    where
+        S: System + 'static,
+    {
+        let name = system.borrow().name();
+        if !self.replace_system(system) {
+            return false;
+        }
+        self.set_refresh(name, RefreshPeriod::EveryTime);
+        true
+    }
+
+    ///
+    /// Remove all systems, [`System::on_stop`] is called for each
+    pub fn shutdown(&mut self) {
+        self.names.clear();
+        self.refresh.clear();
+        self.timings.clear();
+        self.priorities.clear();
+        self.stages.clear();
+        for system in self.systems.drain(..) {
+            system.borrow_mut().on_stop();
+        }
     }
 
     ///
@@ -157,35 +552,268 @@ impl SystemManager {
         }
     }
 
+    ///
+    /// Get refresh time for a system
+    pub fn get_refresh(&self, name: &str) -> Option<RefreshPeriod> {
+        self.names
+            .get(&name)
+            .map(|id| *self.refresh.get(*id).unwrap().borrow())
+    }
+
+    ///
+    /// Get run time of a system, `None` if unknown or never executed.
+    pub fn timing(&self, name: &str) -> Option<SystemTiming> {
+        self.names.get(name).and_then(|id| self.timings[*id].get())
+    }
+
+    ///
+    /// Iterate over system names, in registration order.
+    pub fn system_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.systems.iter().map(|system| system.borrow().name())
+    }
+
+    ///
+    /// Get the number of systems.
+    pub fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    ///
+    /// Check if there is no system.
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
+
+    ///
+    /// Check that components required by systems are declared by `EntityManagerComponentType`
+    /// (cf [`crate::create_entity_manager_component`] and [`crate::EntityManager::schema`]).
+    ///
+    /// # Return
+    ///
+    /// The missing components, as `(system name, component name)`, empty if all are satisfied.
+    pub fn validate<EntityManagerComponentType>(&self) -> Vec<(&'static str, &'static str)>
+    where
+        EntityManagerComponentType: EntityManagerComponent,
+    {
+        let components = EntityManagerComponentType::schema();
+        let mut missing = Vec::new();
+        for system in self.systems.iter() {
+            let system = system.borrow();
+            for component in system.required_components() {
+                if !components.contains(component) {
+                    missing.push((system.name(), *component));
+                }
+            }
+        }
+        missing
+    }
+
     fn set_refresh_by_pos(&self, id: usize, value: RefreshPeriod) {
         let mut status = self.refresh.get(id).unwrap().borrow_mut();
         *status = value;
     }
 
     ///
-    /// Execute all systems
-    pub fn update<EventAdapters>(
+    /// Execute all systems, stage by stage
+    ///
+    /// A failing system is stopped (cf [`SystemManager::set_refresh`] to restart it),
+    /// the others are still executed.
+    ///
+    /// A system with [`RefreshPeriod::At`] is executed only once its date is reached
+    /// (it was executed while the date was in the future before [`SystemManager::run_until`]).
+    ///
+    /// # Return
+    ///
+    /// The max refresh period of all systems, [`RefreshPeriod::Stop`] only if all systems are stopped.
+    /// Or errors of failing systems.
+    pub fn update<EventAdapters, Context>(
+        &self,
+        event_dispatcher: &Rc<EventDispatcher<EventAdapters, Context>>,
+    ) -> Result<RefreshPeriod, Vec<SystemError>>
+    where
+        EventAdapters: Default,
+        Context: 'static,
+    {
+        self.frame.increment();
+        let (ret, _, errors) = self.run_systems(event_dispatcher, self.all_ids(), None);
+        if errors.is_empty() {
+            Ok(ret)
+        } else {
+            Err(errors)
+        }
+    }
+
+    ///
+    /// Execute systems of a stage, cf [`SystemManager::update`].
+    ///
+    /// The frame number is not incremented, so a stage could be executed several times per frame.
+    ///
+    /// # Return
+    ///
+    /// The max refresh period of the stage systems, [`RefreshPeriod::Stop`] if the stage is unknown.
+    /// Or errors of failing systems.
+    pub fn run_stage<EventAdapters, Context>(
+        &self,
+        stage: &str,
+        event_dispatcher: &Rc<EventDispatcher<EventAdapters, Context>>,
+    ) -> Result<RefreshPeriod, Vec<SystemError>>
+    where
+        EventAdapters: Default,
+        Context: 'static,
+    {
+        let ids = self
+            .stages
+            .iter()
+            .filter(|(name, _)| name == stage)
+            .flat_map(|(_, ids)| ids.iter().copied());
+        let (ret, _, errors) = self.run_systems(event_dispatcher, ids, None);
+        if errors.is_empty() {
+            Ok(ret)
+        } else {
+            Err(errors)
+        }
+    }
+
+    ///
+    /// Execute systems, without starting a new one once `budget` is elapsed.
+    ///
+    /// The first due system is always executed, even with a zero budget.
+    ///
+    /// Deferred systems keep their refresh period, so they are still due at next call.
+    /// Cf [`SystemManager::update`].
+    ///
+    /// # Return
+    ///
+    /// `true` if all due systems have been executed.
+    /// Or errors of failing systems.
+    pub fn update_with_budget<EventAdapters, Context>(
+        &self,
+        event_dispatcher: &Rc<EventDispatcher<EventAdapters, Context>>,
+        budget: Duration,
+    ) -> Result<bool, Vec<SystemError>>
+    where
+        EventAdapters: Default,
+        Context: 'static,
+    {
+        self.frame.increment();
+        let (_, completed, errors) =
+            self.run_systems(event_dispatcher, self.all_ids(), Some(budget));
+        if errors.is_empty() {
+            Ok(completed)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn all_ids(&self) -> impl Iterator<Item = usize> + '_ {
+        self.stages.iter().flat_map(|(_, ids)| ids.iter().copied())
+    }
+
+    fn run_systems<EventAdapters, Context>(
         &self,
-        event_dispatcher: &Rc<EventDispatcher<EventAdapters>>,
-    ) -> RefreshPeriod
+        event_dispatcher: &Rc<EventDispatcher<EventAdapters, Context>>,
+        ids: impl Iterator<Item = usize>,
+        budget: Option<Duration>,
+    ) -> (RefreshPeriod, bool, Vec<SystemError>)
     where
         EventAdapters: Default,
+        Context: 'static,
     {
         let mut ret = RefreshPeriod::Stop;
+        let mut completed = true;
+        let mut errors = Vec::new();
+        let mut executed = false;
         let now = Instant::now();
-        for ((id, system), refresh) in self.systems.iter().enumerate().zip(self.refresh.iter()) {
-            let refresh = *refresh.borrow();
+        for id in ids {
+            let system = &self.systems[id];
+            let mut refresh = *self.refresh[id].borrow();
+            if refresh.is_due(now) {
+                // at least one system is executed, so each call makes progress
+                if executed && budget.is_some_and(|budget| now.elapsed() > budget) {
+                    completed = false;
+                } else {
+                    executed = true;
+                    let mut system = system.borrow_mut();
+                    let start = Instant::now();
+                    let result = system.run(now, &self.resources);
+                    let timing = &self.timings[id];
+                    timing.set(Some(SystemTiming::update(timing.get(), start.elapsed())));
+                    let new_refresh = match result {
+                        Ok(new_refresh) => new_refresh,
+                        Err(error) => {
+                            errors.push(SystemError::new(system.name(), error));
+                            RefreshPeriod::Stop
+                        }
+                    };
+                    if new_refresh != refresh {
+                        self.set_refresh_by_pos(id, new_refresh);
+                        refresh = new_refresh;
+                    }
+                    if self.dispatch_mode == DispatchMode::AfterEach {
+                        event_dispatcher.dispatch();
+                    }
+                }
+            }
             ret = max(ret, refresh);
-            if RefreshPeriod::At(now) < refresh {
-                let mut system = system.borrow_mut();
-                let new_refresh = system.run(now);
-                if new_refresh != refresh {
-                    self.set_refresh_by_pos(id, new_refresh);
+        }
+        if self.dispatch_mode == DispatchMode::AfterAll {
+            event_dispatcher.dispatch();
+        }
+        (ret, completed, errors)
+    }
+
+    ///
+    /// Execute all systems until `should_stop` returns true or all systems are stopped.
+    ///
+    /// Between two updates, it sleeps until the next system refresh, without sleeping
+    /// while a system is [`RefreshPeriod::EveryTime`].
+    ///
+    /// # Return
+    ///
+    /// Errors of failing systems, as soon as a system fails.
+    pub fn run_until<EventAdapters, Context, F>(
+        &self,
+        event_dispatcher: &Rc<EventDispatcher<EventAdapters, Context>>,
+        mut should_stop: F,
+    ) -> Result<(), Vec<SystemError>>
+    where
+        EventAdapters: Default,
+        Context: 'static,
+        F: FnMut() -> bool,
+    {
+        while !should_stop() {
+            if self.update(event_dispatcher)? == RefreshPeriod::Stop {
+                break;
+            }
+            if let RefreshPeriod::At(next) = self.next_refresh() {
+                let now = Instant::now();
+                if next > now {
+                    thread::sleep(next - now);
                 }
-                event_dispatcher.dispatch();
             }
         }
-        ret
+        Ok(())
+    }
+
+    fn next_refresh(&self) -> RefreshPeriod {
+        let mut next = RefreshPeriod::Stop;
+        for refresh in self.refresh.iter().map(|refresh| *refresh.borrow()) {
+            next = match (next, refresh) {
+                (_, RefreshPeriod::EveryTime) => return RefreshPeriod::EveryTime,
+                (RefreshPeriod::At(a), RefreshPeriod::At(b)) => RefreshPeriod::At(a.min(b)),
+                (RefreshPeriod::Stop, refresh) => refresh,
+                (next, _) => next,
+            };
+        }
+        next
+    }
+
+    ///
+    /// Check if all systems are stopped
+    pub fn is_idle(&self) -> bool {
+        self.refresh
+            .iter()
+            .all(|refresh| *refresh.borrow() == RefreshPeriod::Stop)
     }
 }
 
@@ -0,0 +1,6 @@
+//! Reusable systems, to register in a [`crate::SystemManager`].
+//!
+
+mod lifetime;
+
+pub use lifetime::*;
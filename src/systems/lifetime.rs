@@ -0,0 +1,116 @@
+use crate::entity_manager::{Component, EntityManager, EntityManagerComponent, StorageAccess};
+use crate::resources::Resources;
+use crate::storage::{BasicVecStorage, Storage};
+use crate::system_manager::{RefreshPeriod, System, SystemResult};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+///
+/// Time to live of an entity, decreased by [`LifetimeSystem`].
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct Lifetime {
+    ///
+    /// Remaining time before the entity is deleted
+    pub remaining: Duration,
+}
+
+impl Lifetime {
+    ///
+    /// Create a `Lifetime`
+    pub fn new(remaining: Duration) -> Self {
+        Self { remaining }
+    }
+}
+
+impl Component for Lifetime {
+    type Storage = BasicVecStorage<Self>;
+}
+
+///
+/// Decrease [`Lifetime`] of entities by the elapsed time since the previous run,
+/// and delete entities whose lifetime reaches zero.
+///
+/// # Examples
+/// ```rust
+/// use entity_system::systems::{Lifetime, LifetimeSystem};
+/// use entity_system::{create_entity_manager_component, SystemManager};
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+/// use std::time::Duration;
+///
+/// create_entity_manager_component!(EMC { Lifetime });
+/// type EntityManager = entity_system::EntityManager<EMC>;
+///
+/// let entity_manager = Rc::new(RefCell::new(EntityManager::new()));
+/// let bullet = entity_manager.borrow_mut().create_entity();
+/// entity_manager
+///     .borrow()
+///     .add_component_with::<Lifetime, _>(bullet, |lifetime| {
+///         lifetime.remaining = Duration::from_secs(2)
+///     });
+///
+/// let mut system_manager = SystemManager::new();
+/// system_manager.add_system(Rc::new(RefCell::new(LifetimeSystem::new(entity_manager))));
+/// ```
+pub struct LifetimeSystem<EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default,
+{
+    entity_manager: Rc<RefCell<EntityManager<EntityManagerComponentType>>>,
+    last_run: Option<Instant>,
+}
+
+impl<EntityManagerComponentType> LifetimeSystem<EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default + StorageAccess<Lifetime>,
+{
+    ///
+    /// Create a `LifetimeSystem`
+    pub fn new(entity_manager: Rc<RefCell<EntityManager<EntityManagerComponentType>>>) -> Self {
+        Self {
+            entity_manager,
+            last_run: None,
+        }
+    }
+}
+
+impl<EntityManagerComponentType> System for LifetimeSystem<EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default + StorageAccess<Lifetime>,
+{
+    fn name(&self) -> &'static str {
+        "lifetime"
+    }
+
+    fn run(&mut self, now: Instant, _resources: &Resources) -> SystemResult {
+        let delta = self.last_run.map_or(Duration::ZERO, |last_run| {
+            now.saturating_duration_since(last_run)
+        });
+        self.last_run = Some(now);
+
+        let mut expired = Vec::new();
+        {
+            let entity_manager = self.entity_manager.borrow();
+            let mut storage = entity_manager.storage_mut::<Lifetime>();
+            storage.for_each_mut(&mut |entity, lifetime| {
+                lifetime.remaining = lifetime.remaining.saturating_sub(delta);
+                if lifetime.remaining.is_zero() && entity_manager.is_alive(entity) {
+                    expired.push(entity);
+                }
+            });
+        }
+
+        if !expired.is_empty() {
+            let mut entity_manager = self.entity_manager.borrow_mut();
+            for entity in expired {
+                entity_manager.delete_entity(entity);
+            }
+        }
+        Ok(RefreshPeriod::EveryTime)
+    }
+
+    fn required_components(&self) -> &'static [&'static str] {
+        &["Lifetime"]
+    }
+}
@@ -0,0 +1,175 @@
+use crate::entity::Entity;
+use crate::entity_manager::{Component, EntityManager, EntityManagerComponent, StorageAccess};
+use crate::storage::Storage;
+use std::any::type_name;
+
+type ComponentOperation<EntityManagerComponentType> =
+    Box<dyn FnOnce(&EntityManager<EntityManagerComponentType>)>;
+
+///
+/// Kind of component difference, cf [`WorldDiff::components`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ComponentChange {
+    /// the component exists only in the target
+    Added,
+    /// the component exists only in the source
+    Removed,
+    /// the component value differs
+    Changed,
+}
+
+///
+/// Differences between two [`EntityManager`], cf [`EntityManager::diff`].
+///
+/// It can be applied to the source (or a copy of it) to move it toward the target,
+/// cf [`EntityManager::apply_diff`].
+pub struct WorldDiff<EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default,
+{
+    pub(crate) created: Vec<Entity>,
+    pub(crate) destroyed: Vec<Entity>,
+    components: Vec<(Entity, &'static str, ComponentChange)>,
+    operations: Vec<ComponentOperation<EntityManagerComponentType>>,
+}
+
+impl<EntityManagerComponentType> WorldDiff<EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default + 'static,
+{
+    pub(crate) fn new(created: Vec<Entity>, destroyed: Vec<Entity>) -> Self {
+        Self {
+            created,
+            destroyed,
+            components: Vec::new(),
+            operations: Vec::new(),
+        }
+    }
+
+    ///
+    /// Get entities alive only in the target.
+    pub fn created(&self) -> &[Entity] {
+        &self.created
+    }
+
+    ///
+    /// Get entities alive only in the source.
+    pub fn destroyed(&self) -> &[Entity] {
+        &self.destroyed
+    }
+
+    ///
+    /// Get component differences, as `(entity, component type name, change)`.
+    ///
+    /// The type name is given by [`std::any::type_name`].
+    pub fn components(&self) -> &[(Entity, &'static str, ComponentChange)] {
+        &self.components
+    }
+
+    ///
+    /// Check if there is no difference.
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.destroyed.is_empty() && self.components.is_empty()
+    }
+
+    ///
+    /// Compare component `T` of entities between `source` and `target`.
+    pub(crate) fn compare<T>(
+        &mut self,
+        source: &EntityManager<EntityManagerComponentType>,
+        target: &EntityManager<EntityManagerComponentType>,
+        entities: &[Entity],
+    ) where
+        EntityManagerComponentType: StorageAccess<T>,
+        T: Component + PartialEq + Clone + 'static,
+    {
+        let source_storage = source.storage::<T>();
+        let target_storage = target.storage::<T>();
+        for entity in entities.iter().copied() {
+            let before = source_storage
+                .try_get(entity)
+                .filter(|_| source.is_alive(entity));
+            let after = target_storage
+                .try_get(entity)
+                .filter(|_| target.is_alive(entity));
+            let change = match (before, after) {
+                (None, Some(_)) => ComponentChange::Added,
+                (Some(_), None) => ComponentChange::Removed,
+                (Some(before), Some(after)) if before != after => ComponentChange::Changed,
+                _ => continue,
+            };
+            self.components.push((entity, type_name::<T>(), change));
+            match after {
+                Some(after) => {
+                    let value = after.clone();
+                    self.operations.push(Box::new(move |entity_manager| {
+                        if !entity_manager.has_component::<T>(entity) {
+                            entity_manager.add_component::<T>(entity);
+                        }
+                        *entity_manager.get_component_mut::<T>(entity) = value;
+                    }));
+                }
+                // destroyed entities lose all their components
+                None if !target.is_alive(entity) => {}
+                None => self.operations.push(Box::new(move |entity_manager| {
+                    entity_manager.remove_component::<T>(entity)
+                })),
+            }
+        }
+    }
+
+    pub(crate) fn apply_components(
+        self,
+        entity_manager: &EntityManager<EntityManagerComponentType>,
+    ) {
+        for operation in self.operations {
+            operation(entity_manager);
+        }
+    }
+}
+
+///
+/// Tuple of components compared by [`EntityManager::diff`].
+///
+/// Implemented for tuples up to 6 components, each one must implement `PartialEq` and `Clone`.
+pub trait ComponentDiff<EntityManagerComponentType>
+where
+    EntityManagerComponentType: EntityManagerComponent + Default,
+{
+    ///
+    /// Compare components of entities between `source` and `target`.
+    fn compare(
+        diff: &mut WorldDiff<EntityManagerComponentType>,
+        source: &EntityManager<EntityManagerComponentType>,
+        target: &EntityManager<EntityManagerComponentType>,
+        entities: &[Entity],
+    );
+}
+
+macro_rules! impl_component_diff {
+    ($($component:ident),*) => {
+        impl<EntityManagerComponentType, $($component),*> ComponentDiff<EntityManagerComponentType> for ($($component,)*)
+        where
+            EntityManagerComponentType: EntityManagerComponent + Default + 'static $(+ StorageAccess<$component>)*,
+            $(
+            $component: Component + PartialEq + Clone + 'static,
+            )*
+        {
+            fn compare(
+                diff: &mut WorldDiff<EntityManagerComponentType>,
+                source: &EntityManager<EntityManagerComponentType>,
+                target: &EntityManager<EntityManagerComponentType>,
+                entities: &[Entity],
+            ) {
+                $(diff.compare::<$component>(source, target, entities);)*
+            }
+        }
+    };
+}
+
+impl_component_diff!(A);
+impl_component_diff!(A, B);
+impl_component_diff!(A, B, C);
+impl_component_diff!(A, B, C, D);
+impl_component_diff!(A, B, C, D, E);
+impl_component_diff!(A, B, C, D, E, F);
@@ -1,4 +1,4 @@
-use entity_system::{Entity, EntityAllocator};
+use entity_system::{AllocError, Entity, EntityAllocator};
 
 #[test]
 fn test_entity_01() {
@@ -30,3 +30,259 @@ fn test_entity_01() {
     v2.sort_unstable();
     assert_eq!(v2, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
 }
+
+#[test]
+fn test_entity_02() {
+    assert!(!Entity::INVALID.is_valid());
+    assert!(Entity::new(0).is_valid());
+
+    let mut ea = EntityAllocator::new();
+    for _ in 0..10 {
+        assert!(ea.alloc().is_valid());
+    }
+}
+
+#[test]
+fn test_entity_03() {
+    let mut ea = EntityAllocator::new();
+    let v1: Vec<_> = ea.alloc_batch(5).iter().map(|e| e.id).collect();
+    assert_eq!(v1, [0, 1, 2, 3, 4]);
+
+    ea.free(Entity::new(1));
+    ea.free(Entity::new(3));
+    let mut v2: Vec<_> = ea.alloc_batch(4).iter().map(|e| e.id).collect();
+    v2.sort_unstable();
+    assert_eq!(v2, [1, 3, 5, 6]);
+
+    assert!(ea.alloc_batch(0).is_empty());
+    assert_eq!(ea.alloc().id, 7);
+    assert_eq!(ea.iter().count(), 8);
+}
+
+#[test]
+fn test_entity_04() {
+    use std::collections::BTreeMap;
+
+    assert!(Entity::new(1) < Entity::new(2));
+    assert!(Entity::new(3) > Entity::new(2));
+
+    let mut v = vec![Entity::new(5), Entity::new(0), Entity::new(3)];
+    v.sort();
+    assert_eq!(v, [Entity::new(0), Entity::new(3), Entity::new(5)]);
+
+    let mut map = BTreeMap::new();
+    map.insert(Entity::new(2), "b");
+    map.insert(Entity::new(1), "a");
+    assert_eq!(map.values().copied().collect::<Vec<_>>(), ["a", "b"]);
+}
+
+#[test]
+fn test_entity_05() {
+    let mut ea = EntityAllocator::with_capacity_limit(3);
+    let e1 = ea.try_alloc().unwrap();
+    ea.alloc_batch(2);
+    assert_eq!(ea.len(), 3);
+    assert!(ea.try_alloc().is_none());
+
+    ea.free(e1);
+    assert_eq!(ea.try_alloc(), Some(e1));
+    assert!(ea.try_alloc().is_none());
+
+    let mut ea = EntityAllocator::new();
+    assert!(ea.is_empty());
+    assert!(ea.try_alloc().is_some());
+}
+
+#[test]
+#[should_panic(expected = "no more entity available")]
+fn test_entity_06() {
+    let mut ea = EntityAllocator::with_capacity_limit(2);
+    ea.alloc_batch(3);
+}
+
+#[test]
+fn test_entity_07() {
+    let mut ea = EntityAllocator::new();
+    ea.alloc_batch(5);
+    assert_eq!(ea.free_ids().count(), 0);
+
+    ea.free(Entity::new(3));
+    ea.free(Entity::new(1));
+    assert!(ea.is_free(Entity::new(3)));
+    assert!(!ea.is_free(Entity::new(2)));
+    let mut r: Vec<_> = ea.free_ids().collect();
+    r.sort();
+    assert_eq!(r, [Entity::new(1), Entity::new(3)]);
+
+    let e = ea.alloc();
+    assert!(!ea.is_free(e));
+    assert_eq!(ea.free_ids().count(), 1);
+}
+
+#[test]
+fn test_entity_08() {
+    let mut ea = EntityAllocator::new();
+    let e1 = ea.alloc();
+    let e2 = ea.alloc();
+    let w1 = ea.weak(e1);
+    assert_eq!(w1.entity(), e1);
+    assert_eq!(ea.upgrade(w1), Some(e1));
+    assert!(ea.is_alive(e2));
+
+    ea.free(e1);
+    assert!(!ea.is_alive(e1));
+    assert!(ea.upgrade(w1).is_none());
+    assert!(ea.upgrade(ea.weak(e1)).is_none());
+
+    assert_eq!(ea.alloc(), e1);
+    assert!(ea.upgrade(w1).is_none());
+    assert_eq!(ea.upgrade(ea.weak(e1)), Some(e1));
+    assert!(!ea.is_alive(Entity::new(2)));
+    assert!(ea.upgrade(ea.weak(Entity::new(2))).is_none());
+}
+
+#[test]
+fn test_entity_09() {
+    let mut ea = EntityAllocator::new();
+    ea.set_recycle(false);
+    let e1 = ea.alloc();
+    ea.alloc_batch(2);
+    ea.free(e1);
+    assert_eq!(ea.alloc().id, 3);
+    assert_eq!(ea.alloc_batch(2), [Entity::new(4), Entity::new(5)]);
+    assert!(ea.is_free(e1));
+    assert_eq!(ea.len(), 5);
+    assert_eq!(ea.iter().count(), 5);
+
+    ea.set_recycle(true);
+    assert_eq!(ea.alloc(), e1);
+}
+
+#[test]
+fn test_entity_10() {
+    let mut ea = EntityAllocator::with_capacity_limit(3);
+    let e1 = ea.alloc();
+    assert_eq!(ea.alloc_at(e1.id), Err(AllocError::AlreadyAlive(e1)));
+    assert_eq!(ea.alloc_at(Entity::INVALID.id), Err(AllocError::InvalidId));
+
+    let e4 = ea.alloc_at(4).unwrap();
+    assert_eq!(e4.id, 4);
+    assert_eq!(ea.len(), 2);
+    assert!(ea.is_free(Entity::new(2)));
+    assert_eq!(ea.alloc_at(2), Ok(Entity::new(2)));
+    assert!(!ea.is_free(Entity::new(2)));
+    assert_eq!(ea.alloc_at(1), Err(AllocError::LimitReached));
+
+    ea.free(e1);
+    assert_eq!(ea.alloc_at(1), Ok(Entity::new(1)));
+    let ids: Vec<_> = ea.iter().map(|entity| entity.id).collect();
+    assert_eq!(ids, [1, 2, 4]);
+}
+
+#[test]
+fn test_entity_11() {
+    let mut ea = EntityAllocator::new();
+    let entities = ea.alloc_batch(5);
+    let weak = ea.weak(entities[4]);
+    ea.free(entities[4]);
+    ea.free(entities[3]);
+    ea.free(entities[1]);
+    ea.compact();
+    assert_eq!(ea.len(), 2);
+    assert!(ea.is_free(entities[1]));
+    assert!(!ea.is_free(entities[3]));
+
+    ea.compact();
+    assert_eq!(ea.alloc_batch(3), [entities[1], entities[3], entities[4]]);
+    assert!(ea.upgrade(weak).is_none());
+
+    for entity in entities.iter() {
+        ea.free(*entity);
+    }
+    ea.compact();
+    assert!(ea.is_empty());
+    assert_eq!(ea.free_ids().count(), 0);
+    assert_eq!(ea.alloc(), entities[0]);
+}
+
+#[test]
+fn test_entity_12() {
+    let mut ea = EntityAllocator::new();
+    assert_eq!(ea.live_count(), 0);
+    assert_eq!(ea.high_water(), 0);
+
+    let entities = ea.alloc_batch(6);
+    ea.free(entities[1]);
+    ea.free(entities[5]);
+    ea.park(entities[2]);
+    assert_eq!(ea.live_count(), 3);
+    assert_eq!(ea.high_water(), 6);
+
+    ea.compact();
+    assert_eq!(ea.live_count(), 3);
+    assert_eq!(ea.high_water(), 5);
+
+    ea.alloc();
+    assert_eq!(ea.live_count(), 4);
+    assert_eq!(ea.high_water(), 5);
+}
+
+#[test]
+fn test_entity_13() {
+    let mut ea = EntityAllocator::new();
+    let e1 = ea.alloc();
+    let weak = ea.weak(e1);
+
+    ea.free(Entity::INVALID);
+    ea.free(Entity::new(1));
+    ea.free(Entity::new(1000));
+    assert_eq!(ea.len(), 1);
+    assert_eq!(ea.free_ids().count(), 0);
+    assert_eq!(ea.upgrade(weak), Some(e1));
+
+    ea.free(e1);
+    ea.free(e1);
+    assert!(ea.upgrade(weak).is_none());
+    assert_eq!(ea.free_ids().count(), 1);
+    assert!(ea.is_empty());
+}
+
+#[test]
+fn test_entity_14() {
+    let mut ea = EntityAllocator::new();
+    ea.free(Entity::new(5));
+    let e0 = ea.alloc();
+    assert_eq!(e0, Entity::new(0));
+    assert!(ea.is_alive(e0));
+
+    ea.free(Entity::new(10));
+    ea.free(Entity::new(11));
+    assert_eq!(ea.len(), 1);
+    assert_eq!(ea.iter().collect::<Vec<_>>(), [e0]);
+}
+
+#[test]
+fn test_entity_15() {
+    let mut ea = EntityAllocator::new();
+    let e0 = ea.alloc();
+    let last = ea.alloc_at(u32::MAX - 1).unwrap();
+    assert_eq!(ea.len(), 2);
+    assert_eq!(ea.high_water(), u32::MAX);
+    assert!(ea.is_free(Entity::new(1_000_000)));
+    assert!(!ea.is_alive(Entity::new(1_000_000)));
+    assert_eq!(ea.iter().collect::<Vec<_>>(), [e0, last]);
+
+    let middle = ea.alloc_at(1_000_000).unwrap();
+    assert!(ea.is_alive(middle));
+    assert!(ea.is_free(Entity::new(999_999)));
+    assert!(ea.is_free(Entity::new(1_000_001)));
+    assert_eq!(ea.iter().collect::<Vec<_>>(), [e0, middle, last]);
+    assert_eq!(ea.alloc(), Entity::new(1));
+
+    ea.free(last);
+    ea.free(middle);
+    ea.compact();
+    assert_eq!(ea.high_water(), 2);
+    assert_eq!(ea.free_ids().count(), 0);
+    assert_eq!(ea.alloc(), Entity::new(2));
+}
@@ -1,8 +1,12 @@
 use entity_system::{
-    create_entity_manager_component, BasicVecStorage, Component, EntityManager, Query,
+    create_entity_manager_component, create_event_adapters, BasicVecStorage, CachedQuery,
+    CommandBuffer, Component, ComponentChange, Entity, EntityCreated, EntityDeleted, EntityManager,
+    EventDispatcher, Opt, Query, Storage, TrackedVecStorage,
 };
+use std::cell::RefCell;
+use std::rc::Rc;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 struct Position {
     x: u32,
     y: u32,
@@ -12,7 +16,7 @@ impl Component for Position {
     type Storage = BasicVecStorage<Position>;
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy)]
 struct Velocity {
     x: i32,
     y: i32,
@@ -22,7 +26,20 @@ impl Component for Velocity {
     type Storage = BasicVecStorage<Velocity>;
 }
 
-create_entity_manager_component!(EMC { Position, Velocity });
+#[derive(Default, Clone)]
+struct Health {
+    value: u32,
+}
+
+impl Component for Health {
+    type Storage = TrackedVecStorage<Health>;
+}
+
+create_entity_manager_component!(EMC {
+    Position,
+    Velocity,
+    Health
+});
 type MyEntityManager = EntityManager<EMC>;
 
 #[test]
@@ -139,3 +156,1519 @@ fn test_entity_manager_02() {
         assert_eq!(r.len(), 1);
     }
 }
+
+#[test]
+fn test_entity_manager_03() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e1);
+
+    let mut query = Query::new();
+    query.check_component::<Position>();
+    let mut cached_query = CachedQuery::new(query);
+    assert!(!cached_query.query().is_volatile());
+    assert!(cached_query.is_dirty(&entity_manager));
+    assert_eq!(cached_query.entities(&entity_manager), &[e1]);
+    assert!(!cached_query.is_dirty(&entity_manager));
+
+    let e2 = entity_manager.create_entity();
+    assert!(cached_query.is_dirty(&entity_manager));
+    assert_eq!(cached_query.entities(&entity_manager), &[e1]);
+
+    entity_manager.add_component::<Position>(e2);
+    assert_eq!(cached_query.entities(&entity_manager), &[e1, e2]);
+
+    entity_manager.remove_component::<Position>(e1);
+    assert_eq!(cached_query.entities(&entity_manager), &[e2]);
+
+    entity_manager.delete_entity(e2);
+    assert!(cached_query.entities(&entity_manager).is_empty());
+
+    let mut query = Query::<EMC>::new();
+    query.check_component_by::<Position, _>(|position| position.x > 2);
+    let cached_query = CachedQuery::new(query);
+    assert!(cached_query.query().is_volatile());
+}
+
+#[test]
+fn test_entity_manager_04() {
+    let mut entity_manager = MyEntityManager::new();
+    let e = entity_manager.create_entity();
+
+    entity_manager.get_or_add_component_mut::<Position>(e).x = 5;
+    assert!(entity_manager.has_component::<Position>(e));
+    assert_eq!(entity_manager.get_component::<Position>(e).x, 5);
+
+    {
+        let position = entity_manager.get_or_add_component_with::<Position, _>(e, |position| {
+            position.x = 10;
+        });
+        assert_eq!(position.x, 5);
+    }
+
+    {
+        let velocity = entity_manager.get_or_add_component_with::<Velocity, _>(e, |velocity| {
+            velocity.y = -3;
+        });
+        assert_eq!(velocity.y, -3);
+    }
+    assert!(entity_manager.has_component::<Velocity>(e));
+}
+
+#[test]
+fn test_entity_manager_05() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    let e3 = entity_manager.create_entity();
+    entity_manager.add_component::<Health>(e1);
+    entity_manager.add_component::<Health>(e2);
+    assert_eq!(entity_manager.tick(), 0);
+
+    let changed: Vec<_> = entity_manager
+        .iter_changed::<Health>(0)
+        .map(|(entity, _)| entity)
+        .collect();
+    assert_eq!(changed, [e1, e2]);
+
+    let tick = entity_manager.advance_tick();
+    assert_eq!(tick, 1);
+    assert_eq!(entity_manager.iter_changed::<Health>(tick).count(), 0);
+
+    entity_manager.update_component_with::<Health, _>(e2, |health| health.value = 5);
+    entity_manager.add_component::<Health>(e3);
+    {
+        let _ = entity_manager.get_component::<Health>(e1);
+    }
+    let changed: Vec<_> = entity_manager
+        .iter_changed::<Health>(tick)
+        .map(|(entity, health)| (entity, health.value))
+        .collect();
+    assert_eq!(changed, [(e2, 5), (e3, 0)]);
+
+    entity_manager.delete_entity(e2);
+    let changed: Vec<_> = entity_manager
+        .iter_changed::<Health>(tick)
+        .map(|(entity, _)| entity)
+        .collect();
+    assert_eq!(changed, [e3]);
+}
+
+#[test]
+fn test_entity_manager_06() {
+    let mut entity_manager = MyEntityManager::new();
+    let e = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e, |position| position.x = 5);
+    assert!(entity_manager
+        .try_components_of::<(Position, Velocity)>(e)
+        .is_none());
+
+    entity_manager.add_component_with::<Velocity, _>(e, |velocity| velocity.y = -2);
+    {
+        let (position, velocity) = entity_manager.components_of::<(Position, Velocity)>(e);
+        assert_eq!(position.x, 5);
+        assert_eq!(velocity.y, -2);
+    }
+
+    let (velocity, position) = entity_manager
+        .try_components_of::<(Velocity, Position)>(e)
+        .unwrap();
+    assert_eq!(position.x, 5);
+    assert_eq!(velocity.y, -2);
+}
+
+#[test]
+fn test_entity_manager_07() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    let e3 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e1);
+    entity_manager.add_component::<Position>(e2);
+    entity_manager.add_component::<Velocity>(e2);
+    entity_manager.add_component::<Velocity>(e3);
+
+    let mut r: Vec<_> = entity_manager.iter_archetype::<(Position,)>().collect();
+    r.sort_by_key(|entity| entity.id);
+    assert_eq!(r, [e1, e2]);
+
+    let r: Vec<_> = entity_manager
+        .iter_archetype::<(Velocity, Position)>()
+        .collect();
+    assert_eq!(r, [e2]);
+
+    entity_manager.remove_component::<Position>(e2);
+    entity_manager.get_or_add_component_mut::<Velocity>(e1);
+    let r: Vec<_> = entity_manager
+        .iter_archetype::<(Position, Velocity)>()
+        .collect();
+    assert_eq!(r, [e1]);
+
+    entity_manager.delete_entity(e1);
+    assert_eq!(
+        entity_manager
+            .iter_archetype::<(Position, Velocity)>()
+            .count(),
+        0
+    );
+    let mut r: Vec<_> = entity_manager.iter_archetype::<(Velocity,)>().collect();
+    r.sort_by_key(|entity| entity.id);
+    assert_eq!(r, [e2, e3]);
+}
+
+#[test]
+fn test_entity_manager_08() {
+    let mut entity_manager = MyEntityManager::new();
+    let entities: Vec<_> = (0..5).map(|_| entity_manager.create_entity()).collect();
+    entity_manager.add_components_from(
+        entities
+            .iter()
+            .enumerate()
+            .map(|(i, entity)| (*entity, Position { x: i as u32, y: 1 })),
+    );
+
+    for (i, entity) in entities.iter().enumerate() {
+        let position = entity_manager.get_component::<Position>(*entity);
+        assert_eq!(position.x, i as u32);
+        assert_eq!(position.y, 1);
+    }
+    assert_eq!(entity_manager.iter_archetype::<(Position,)>().count(), 5);
+}
+
+#[test]
+fn test_entity_manager_09() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e1);
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e2);
+    entity_manager.add_component_with::<Velocity, _>(e2, |velocity| velocity.x = 1);
+    let e3 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e3);
+    entity_manager.add_component::<Velocity>(e3);
+
+    let mut query = Query::new();
+    query.require::<(Position, Velocity)>();
+    let r: Vec<_> = entity_manager.iter(&query).collect();
+    assert_eq!(r, [e2, e3]);
+
+    query.check_component_by::<Velocity, _>(|velocity| velocity.x > 0);
+    let r: Vec<_> = entity_manager.iter(&query).collect();
+    assert_eq!(r, [e2]);
+}
+
+#[derive(Default)]
+struct Mass(u32);
+entity_system::impl_component!(Mass);
+
+#[derive(Default)]
+struct Charge(i32);
+entity_system::impl_component!(Charge => entity_system::TrackedVecStorage);
+
+create_entity_manager_component!(EMC2 { Mass, Charge });
+
+#[test]
+fn test_entity_manager_10() {
+    let mut entity_manager = EntityManager::<EMC2>::new();
+    let e = entity_manager.create_entity();
+    entity_manager.add_component_with::<Mass, _>(e, |mass| mass.0 = 2);
+    entity_manager.add_component_with::<Charge, _>(e, |charge| charge.0 = -1);
+    assert_eq!(entity_manager.get_component::<Mass>(e).0, 2);
+    assert_eq!(entity_manager.get_component::<Charge>(e).0, -1);
+    assert_eq!(entity_manager.iter_changed::<Charge>(0).count(), 1);
+}
+
+#[test]
+fn test_entity_manager_11() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e1, |position| position.x = 3);
+    entity_manager.add_component_with::<Position, _>(e2, |position| position.y = 4);
+
+    entity_manager.move_component::<Position>(e1, e2);
+    assert!(!entity_manager.has_component::<Position>(e1));
+    {
+        let position = entity_manager.get_component::<Position>(e2);
+        assert_eq!(position.x, 3);
+        assert_eq!(position.y, 0);
+    }
+
+    entity_manager.move_component::<Position>(e2, e2);
+    assert_eq!(entity_manager.get_component::<Position>(e2).x, 3);
+
+    entity_manager.move_component::<Position>(e2, e1);
+    assert_eq!(entity_manager.get_component::<Position>(e1).x, 3);
+    let r: Vec<_> = entity_manager.iter_archetype::<(Position,)>().collect();
+    assert_eq!(r, [e1]);
+}
+
+#[test]
+fn test_entity_manager_12() {
+    use entity_system::Storage;
+
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e1);
+    entity_manager.add_component::<Position>(e2);
+
+    {
+        let mut storage = entity_manager.storage_mut::<Position>();
+        storage.get_mut(e1).x = 1;
+        storage.get_mut(e2).x = 2;
+    }
+
+    let storage = entity_manager.storage::<Position>();
+    let sum: u32 = entity_manager
+        .iter_all()
+        .map(|entity| storage.get(entity).x)
+        .sum();
+    assert_eq!(sum, 3);
+    assert_eq!(entity_manager.get_component::<Position>(e2).x, 2);
+}
+
+#[test]
+fn test_entity_manager_13() {
+    let mut entity_manager = MyEntityManager::with_capacity_limit(2);
+    let e1 = entity_manager.try_create_entity().unwrap();
+    let e2 = entity_manager.try_create_entity().unwrap();
+    let generation = entity_manager.generation();
+    assert!(entity_manager.try_create_entity().is_none());
+    assert_eq!(entity_manager.generation(), generation);
+
+    entity_manager.delete_entity(e1);
+    assert_eq!(entity_manager.try_create_entity(), Some(e1));
+    assert_ne!(e1, e2);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_entity_manager_14() {
+    let mut entity_manager = MyEntityManager::new();
+    let entities = entity_manager.create_entities(1000);
+    for entity in entities.iter() {
+        entity_manager
+            .add_component_with::<Position, _>(*entity, |position| position.x = entity.id);
+        entity_manager.add_component::<Health>(*entity);
+    }
+    entity_manager.remove_component::<Position>(entities[10]);
+
+    entity_manager.par_iter_component_mut::<Position>(|entity, position| {
+        position.y = entity.id * 2;
+    });
+    let tick = entity_manager.advance_tick();
+    entity_manager.par_iter_component_mut::<Health>(|_, health| health.value += 1);
+
+    for entity in entities.iter().filter(|entity| **entity != entities[10]) {
+        let position = entity_manager.get_component::<Position>(*entity);
+        assert_eq!(position.y, position.x * 2);
+    }
+    assert_eq!(entity_manager.iter_changed::<Health>(tick).count(), 1000);
+}
+
+#[test]
+fn test_entity_manager_15() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e1, |position| position.x = 1);
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e2, |position| position.x = 5);
+    let e3 = entity_manager.create_entity();
+
+    let mut query = Query::new();
+    query.check_not_global(|entity_manager, entity| {
+        entity_manager.has_component::<Position>(entity)
+            && entity_manager.get_component::<Position>(entity).x > 2
+    });
+    assert!(query.is_volatile());
+    let r: Vec<_> = entity_manager.iter(&query).collect();
+    assert_eq!(r, [e1, e3]);
+
+    query.check_component::<Position>();
+    let r: Vec<_> = entity_manager.iter(&query).collect();
+    assert_eq!(r, [e1]);
+}
+
+#[test]
+fn test_entity_manager_16() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e1, |position| position.x = 1);
+    entity_manager.add_component_with::<Velocity, _>(e1, |velocity| velocity.x = 2);
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e2, |position| position.x = 3);
+    let e3 = entity_manager.create_entity();
+    entity_manager.add_component::<Velocity>(e3);
+
+    let mut query = Query::new();
+    query.check_component::<Velocity>();
+    entity_manager.for_each_mut::<Position, _>(&query, |entity, position| {
+        position.x += entity_manager.get_component::<Velocity>(entity).x as u32;
+    });
+
+    let mut r = Vec::new();
+    entity_manager.for_each::<Position, _>(&Query::new(), |entity, position| {
+        r.push((entity, position.x))
+    });
+    assert_eq!(r, [(e1, 3), (e2, 3)]);
+
+    let mut query = Query::new();
+    query.check_component_by::<Position, _>(|position| position.x > 2);
+    let mut count = 0;
+    entity_manager.for_each_mut::<Position, _>(&query, |_, position| {
+        position.x = 0;
+        count += 1;
+    });
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn test_entity_manager_17() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    let w1 = entity_manager.weak(e1);
+    let w2 = entity_manager.weak(e2);
+
+    entity_manager.delete_entity(e1);
+    assert!(!entity_manager.is_alive(e1));
+    assert!(entity_manager.upgrade(w1).is_none());
+    assert_eq!(entity_manager.upgrade(w2), Some(e2));
+
+    let e3 = entity_manager.create_entity();
+    assert_eq!(e3, e1);
+    assert!(entity_manager.is_alive(e3));
+    assert!(entity_manager.upgrade(w1).is_none());
+}
+
+#[test]
+fn test_entity_manager_18() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e1);
+    entity_manager.add_component::<Position>(e2);
+    entity_manager.add_component::<Health>(e2);
+
+    let stats = entity_manager.stats();
+    assert_eq!(stats.entities, 2);
+    assert_eq!(
+        stats.components,
+        [("Position", 2), ("Velocity", 0), ("Health", 1)]
+    );
+
+    entity_manager.delete_entity(e2);
+    assert_eq!(
+        format!("{:?}", entity_manager),
+        r#"EntityManager { entities: 1, components: [("Position", 1), ("Velocity", 0), ("Health", 0)] }"#
+    );
+
+    let _storage = entity_manager.storage_mut::<Position>();
+    assert_eq!(
+        format!("{:?}", entity_manager),
+        r#"EntityManager { entities: 1, components: [("Position", <borrowed>), ("Velocity", 0), ("Health", 0)] }"#
+    );
+}
+
+#[test]
+fn test_entity_manager_19() {
+    let mut entity_manager = MyEntityManager::new();
+    let entities = entity_manager.create_entities(6);
+    for entity in entities.iter().step_by(2) {
+        entity_manager.add_component::<Position>(*entity);
+    }
+
+    let mut query = Query::new();
+    query.check_id_range(1..5);
+    let r: Vec<_> = entity_manager.iter(&query).collect();
+    assert_eq!(r, entities[1..5]);
+    assert!(!query.is_volatile());
+
+    query.check_component::<Position>();
+    let r: Vec<_> = entity_manager.iter(&query).collect();
+    assert_eq!(r, [entities[2], entities[4]]);
+}
+
+#[test]
+fn test_entity_manager_20() {
+    let mut entity_manager = MyEntityManager::new();
+    let entities = entity_manager.create_entities(5);
+    for (i, entity) in entities.iter().enumerate() {
+        entity_manager.add_component_with::<Position, _>(*entity, |position| position.x = i as u32);
+    }
+    entity_manager.add_component::<Velocity>(entities[0]);
+
+    let mut query = Query::new();
+    query.check_component_by::<Position, _>(|position| position.x % 2 == 0);
+    entity_manager.retain(&query);
+    let r: Vec<_> = entity_manager.iter_all().collect();
+    assert_eq!(r, [entities[0], entities[2], entities[4]]);
+    assert!(!entity_manager.has_component::<Position>(entities[1]));
+
+    entity_manager.retain(Query::new().check_component::<Velocity>());
+    let r: Vec<_> = entity_manager.iter_all().collect();
+    assert_eq!(r, [entities[0]]);
+    assert_eq!(entity_manager.iter_archetype::<(Position,)>().count(), 1);
+}
+
+#[cfg(feature = "profiling")]
+#[test]
+fn test_entity_manager_21() {
+    let mut entity_manager = MyEntityManager::new();
+    let e = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e);
+    entity_manager.add_component::<Health>(e);
+    entity_manager.get_component::<Position>(e);
+    entity_manager.get_component::<Position>(e);
+    entity_manager.update_component_with::<Health, _>(e, |health| health.value = 1);
+
+    let stats = entity_manager.storage::<Position>().access_stats();
+    assert_eq!(stats.alloc, 1);
+    assert_eq!(stats.get, 2);
+    assert_eq!(stats.get_mut, 0);
+    assert_eq!(entity_manager.storage::<Health>().access_stats().get_mut, 1);
+
+    entity_manager.reset_access_stats();
+    entity_manager.delete_entity(e);
+    let stats = entity_manager.storage::<Position>().access_stats();
+    assert_eq!(stats.free, 1);
+    assert_eq!(stats.get, 0);
+}
+
+#[test]
+fn test_entity_manager_22() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e1, |position| position.x = 1);
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e2, |position| position.x = 10);
+    entity_manager.create_entity();
+
+    let mut base = Query::new();
+    base.check_component::<Position>();
+    let mut near = base.clone();
+    near.check_component_by::<Position, _>(|position| position.x < 5);
+    let mut far = base.clone();
+    far.check_component_by::<Position, _>(|position| position.x >= 5);
+
+    assert!(!base.is_volatile());
+    assert!(near.is_volatile());
+    assert_eq!(entity_manager.iter(&base).collect::<Vec<_>>(), [e1, e2]);
+    assert_eq!(entity_manager.iter(&near).collect::<Vec<_>>(), [e1]);
+    assert_eq!(entity_manager.iter(&far).collect::<Vec<_>>(), [e2]);
+}
+
+#[test]
+fn test_entity_manager_23() {
+    let mut server = MyEntityManager::new();
+    let mut client = MyEntityManager::new();
+    let e1 = server.create_entity();
+    let e2 = server.create_entity();
+    assert_eq!(client.create_entities(2), [e1, e2]);
+    server.add_component_with::<Position, _>(e1, |position| position.x = 3);
+    server.add_component_with::<Position, _>(e2, |position| position.y = 4);
+    client.add_component_with::<Position, _>(e2, |position| position.x = 7);
+
+    server.copy_component_to::<Position>(&client, e1);
+    server.copy_component_to::<Position>(&client, e2);
+    assert_eq!(client.get_component::<Position>(e1).x, 3);
+    assert_eq!(client.get_component::<Position>(e2).x, 0);
+    assert_eq!(client.get_component::<Position>(e2).y, 4);
+    assert_eq!(client.iter_archetype::<(Position,)>().count(), 2);
+    assert_eq!(server.get_component::<Position>(e1).x, 3);
+}
+
+#[test]
+fn test_entity_manager_24() {
+    let mut entity_manager = MyEntityManager::new();
+    assert!(entity_manager.collect(&Query::new()).is_empty());
+    let entities = entity_manager.create_entities(4);
+    entity_manager.add_component::<Velocity>(entities[1]);
+    entity_manager.add_component::<Velocity>(entities[3]);
+
+    let mut query = Query::new();
+    query.check_component::<Velocity>();
+    assert_eq!(entity_manager.collect(&query), [entities[1], entities[3]]);
+    assert_eq!(entity_manager.collect(&Query::new()), entities);
+}
+
+#[test]
+fn test_entity_manager_25() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e1, |position| position.x = 1);
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e2, |position| position.x = 4);
+    entity_manager.create_entity();
+
+    let mut query = Query::new();
+    query.check_component_mut_by::<Position, _>(|position| {
+        position.y = position.x * 2;
+        position.y > 4
+    });
+    assert!(query.is_volatile());
+    assert_eq!(entity_manager.collect(&query), [e2]);
+    assert_eq!(entity_manager.get_component::<Position>(e1).y, 2);
+    assert_eq!(entity_manager.get_component::<Position>(e2).y, 8);
+}
+
+#[test]
+fn test_entity_manager_26() {
+    let mut entity_manager = MyEntityManager::new();
+    let entities = entity_manager.create_entities(4);
+    for entity in entities.iter() {
+        entity_manager.add_component::<Position>(*entity);
+        entity_manager.add_component::<Health>(*entity);
+    }
+    entity_manager.add_component::<Velocity>(entities[0]);
+    entity_manager.add_component::<Velocity>(entities[1]);
+
+    let mut query = Query::new();
+    query.check_component::<Velocity>();
+    entity_manager.remove_component_matching::<Position>(&query);
+    let r: Vec<_> = entity_manager.iter_archetype::<(Position,)>().collect();
+    assert_eq!(r.len(), 2);
+    assert!(!entity_manager.has_component::<Position>(entities[0]));
+    assert!(entity_manager.has_component::<Position>(entities[2]));
+
+    entity_manager.remove_component_all::<Health>();
+    assert_eq!(entity_manager.iter_archetype::<(Health,)>().count(), 0);
+    assert_eq!(entity_manager.stats().components[2], ("Health", 0));
+    assert!(!entity_manager.has_component::<Health>(entities[3]));
+
+    entity_manager.add_component::<Health>(entities[3]);
+    assert!(entity_manager.has_component::<Health>(entities[3]));
+    assert_eq!(entity_manager.iter_archetype::<(Health,)>().count(), 1);
+}
+
+create_event_adapters!(EventAdapters {
+    EntityCreated,
+    EntityDeleted
+});
+
+#[test]
+fn test_entity_manager_27() {
+    let created = Rc::new(RefCell::new(Vec::new()));
+    let mut entity_manager = MyEntityManager::new();
+    let created_clone = created.clone();
+    entity_manager.set_on_create(move |entity| created_clone.borrow_mut().push(entity));
+    let e1 = entity_manager.create_entity();
+    let entities = entity_manager.create_entities(2);
+    assert_eq!(*created.borrow(), [e1, entities[0], entities[1]]);
+
+    let event_dispatcher = EventDispatcher::<EventAdapters>::new();
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let events_created = events.clone();
+    event_dispatcher
+        .connect_fn(move |event: &EntityCreated| events_created.borrow_mut().push((true, event.0)));
+    let events_deleted = events.clone();
+    event_dispatcher.connect_fn(move |event: &EntityDeleted| {
+        events_deleted.borrow_mut().push((false, event.0))
+    });
+    entity_manager.set_dispatcher(&event_dispatcher);
+
+    let e2 = entity_manager.create_entity();
+    entity_manager.delete_entity(e1);
+    assert!(events.borrow().is_empty());
+    event_dispatcher.dispatch();
+    assert_eq!(*events.borrow(), [(true, e2), (false, e1)]);
+    assert_eq!(created.borrow().len(), 3);
+}
+
+#[test]
+#[should_panic(expected = "Position storage already borrowed")]
+fn test_entity_manager_28() {
+    let mut entity_manager = MyEntityManager::new();
+    let e = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e);
+    let _position = entity_manager.get_component_mut::<Position>(e);
+    let _other = entity_manager.get_component_mut::<Position>(e);
+}
+
+#[derive(Default)]
+struct Mana;
+
+#[derive(Default)]
+struct Gauge<Kind> {
+    value: u32,
+    kind: std::marker::PhantomData<Kind>,
+}
+
+entity_system::impl_component!(<Kind> Gauge<Kind> where Kind: Default);
+
+create_entity_manager_component!(GaugeEMC {
+    Position,
+    Gauge<Health>,
+    Gauge<Mana>
+});
+
+#[test]
+fn test_entity_manager_29() {
+    let mut entity_manager = EntityManager::<GaugeEMC>::new();
+    let e = entity_manager.create_entity();
+    entity_manager.add_component::<Gauge<Health>>(e);
+    entity_manager.get_component_mut::<Gauge<Health>>(e).value = 5;
+    assert!(!entity_manager.has_component::<Gauge<Mana>>(e));
+
+    entity_manager.add_component::<Gauge<Mana>>(e);
+    assert_eq!(entity_manager.get_component::<Gauge<Health>>(e).value, 5);
+    assert_eq!(entity_manager.get_component::<Gauge<Mana>>(e).value, 0);
+    assert_eq!(
+        entity_manager.stats().components,
+        [("Position", 0), ("Gauge<Health>", 1), ("Gauge<Mana>", 1)]
+    );
+}
+
+#[test]
+fn test_entity_manager_30() {
+    let mut entity_manager = MyEntityManager::new();
+    let entities = entity_manager.create_entities(6);
+    entity_manager.delete_entity(entities[4]);
+    entity_manager.delete_entity(entities[1]);
+    entity_manager.delete_entity(entities[2]);
+    entity_manager.create_entity();
+    entity_manager.create_entity();
+
+    let ids: Vec<_> = entity_manager.iter_all().map(|entity| entity.id).collect();
+    assert_eq!(ids.len(), 5);
+    assert!(ids.windows(2).all(|ids| ids[0] < ids[1]));
+}
+
+#[test]
+fn test_entity_manager_31() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    let e3 = entity_manager.create_entity();
+    for entity in [e1, e2, e3] {
+        entity_manager.add_component::<Velocity>(entity);
+        entity_manager.get_component_mut::<Velocity>(entity).x = 3;
+    }
+    entity_manager.add_component::<Health>(e1);
+    entity_manager.add_component::<Health>(e3);
+
+    let mut query = Query::new();
+    query.check_component::<Health>();
+    entity_manager.map_component::<Velocity, _>(&query, |velocity| velocity.x = -velocity.x);
+    assert_eq!(entity_manager.get_component::<Velocity>(e1).x, -3);
+    assert_eq!(entity_manager.get_component::<Velocity>(e2).x, 3);
+    assert_eq!(entity_manager.get_component::<Velocity>(e3).x, -3);
+}
+
+#[test]
+fn test_entity_manager_32() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e1);
+    entity_manager.get_component_mut::<Position>(e1).x = 4;
+
+    let view = entity_manager.as_view();
+    let mut query = Query::new();
+    query.check_component::<Position>();
+    assert_eq!(view.count(&query), 1);
+    assert_eq!(view.iter(&query).collect::<Vec<_>>(), [e1]);
+    assert_eq!(view.iter_all().collect::<Vec<_>>(), [e1, e2]);
+    assert!(view.has_component::<Position>(e1));
+    assert!(!view.has_component::<Position>(e2));
+    assert_eq!(view.get_component::<Position>(e1).x, 4);
+}
+
+#[test]
+fn test_entity_manager_33() {
+    let mut entity_manager = MyEntityManager::new();
+    let e = entity_manager.create_entity();
+    assert!(entity_manager.try_add_component::<Health>(e).is_ok());
+    assert!(entity_manager.has_component::<Health>(e));
+    assert_eq!(entity_manager.iter_archetype::<(Health,)>().count(), 1);
+}
+
+#[test]
+fn test_entity_manager_34() {
+    let mut entity_manager = MyEntityManager::new();
+    entity_manager.set_prototype(Position { x: 10, y: 20 });
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    let e3 = entity_manager.create_entity();
+
+    entity_manager.add_component::<Position>(e1);
+    assert_eq!(entity_manager.get_component::<Position>(e1).x, 10);
+    entity_manager.get_component_mut::<Position>(e1).x = 5;
+    entity_manager.add_component::<Position>(e1);
+    assert_eq!(entity_manager.get_component::<Position>(e1).x, 5);
+
+    entity_manager.add_component_with::<Position, _>(e2, |position| position.x += 1);
+    assert_eq!(entity_manager.get_component::<Position>(e2).x, 11);
+    assert_eq!(entity_manager.get_component::<Position>(e2).y, 20);
+
+    assert_eq!(
+        entity_manager.get_or_add_component_mut::<Position>(e3).y,
+        20
+    );
+
+    let e4 = entity_manager.create_entity();
+    entity_manager.add_component::<Velocity>(e4);
+    assert_eq!(entity_manager.get_component::<Velocity>(e4).x, 0);
+}
+
+#[test]
+fn test_entity_manager_35() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Health, _>(e1, |health| health.value = 1);
+    entity_manager.add_component_with::<Health, _>(e2, |health| health.value = 2);
+    let tick = entity_manager.advance_tick();
+
+    entity_manager.swap_component::<Health>(e1, e2);
+    assert_eq!(entity_manager.get_component::<Health>(e1).value, 2);
+    assert_eq!(entity_manager.get_component::<Health>(e2).value, 1);
+    assert_eq!(entity_manager.iter_changed::<Health>(tick).count(), 2);
+}
+
+#[test]
+#[should_panic(
+    expected = "cannot swap test_entity_manager::Position of entities 0 and 1: not allocated"
+)]
+fn test_entity_manager_36() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e1);
+    entity_manager.swap_component::<Position>(e1, e2);
+}
+
+#[test]
+fn test_entity_manager_37() {
+    let mut entity_manager = MyEntityManager::new();
+    let entities = entity_manager.create_entities(10);
+    for entity in entities.iter() {
+        entity_manager.add_component::<Position>(*entity);
+    }
+    for entity in entities[1..].iter() {
+        entity_manager.delete_entity(*entity);
+    }
+    entity_manager.compact();
+    assert_eq!(entity_manager.iter_all().count(), 1);
+    assert_eq!(entity_manager.create_entity(), entities[1]);
+    assert!(!entity_manager.has_component::<Position>(entities[1]));
+    assert!(entity_manager.has_component::<Position>(entities[0]));
+}
+
+#[test]
+fn test_entity_manager_38() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    let e3 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e1, |position| position.x = 1);
+    entity_manager.add_component_with::<Position, _>(e2, |position| position.x = 2);
+    entity_manager.add_component_with::<Position, _>(e3, |position| position.x = 3);
+    entity_manager.add_component::<Health>(e3);
+
+    let query = Query::new()
+        .with_component_by::<Position, _>(|position| position.x > 1)
+        .with_not_component::<Health>()
+        .build();
+    assert!(query.is_volatile());
+    assert_eq!(entity_manager.collect(&query), [e2]);
+
+    assert_eq!(
+        entity_manager.collect(
+            &Query::new()
+                .with_component::<Health>()
+                .with_global(|_, entity| entity.id > 0)
+        ),
+        [e3]
+    );
+}
+
+#[test]
+fn test_entity_manager_39() {
+    let mut entity_manager = MyEntityManager::new();
+    let entities = entity_manager.create_entities(3);
+    entity_manager.delete_entity(entities[1]);
+
+    let mut r = Vec::new();
+    for entity in &entity_manager {
+        r.push(entity);
+    }
+    assert_eq!(r, [entities[0], entities[2]]);
+}
+
+#[test]
+fn test_entity_manager_40() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.spawn_pooled();
+    entity_manager.add_component_with::<Position, _>(e1, |position| position.x = 3);
+    entity_manager.add_component::<Velocity>(e1);
+    let weak = entity_manager.weak(e1);
+
+    entity_manager.despawn_pooled(e1);
+    assert!(!entity_manager.is_alive(e1));
+    assert!(entity_manager.upgrade(weak).is_none());
+    assert_eq!(entity_manager.iter_all().count(), 0);
+    assert_eq!(entity_manager.iter_archetype::<(Position,)>().count(), 0);
+    let e2 = entity_manager.create_entity();
+    assert_ne!(e2, e1);
+
+    let e3 = entity_manager.spawn_pooled();
+    assert_eq!(e3, e1);
+    assert_eq!(entity_manager.get_component::<Position>(e3).x, 3);
+    assert_eq!(
+        entity_manager
+            .iter_archetype::<(Position, Velocity)>()
+            .collect::<Vec<_>>(),
+        [e3]
+    );
+
+    entity_manager.recycle_pooled(e3);
+    assert_eq!(entity_manager.get_component::<Position>(e3).x, 0);
+    assert!(entity_manager.has_component::<Velocity>(e3));
+
+    entity_manager.set_pool_capacity(1);
+    entity_manager.despawn_pooled(e3);
+    entity_manager.despawn_pooled(e2);
+    assert!(!entity_manager.is_alive(e2));
+    let e4 = entity_manager.create_entity();
+    assert_eq!(e4, e2);
+    entity_manager.set_pool_capacity(0);
+    let e5 = entity_manager.spawn_pooled();
+    assert!(!entity_manager.has_component::<Position>(e5));
+}
+
+#[test]
+fn test_entity_manager_41() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    let e3 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Velocity, _>(e1, |velocity| velocity.x = 6);
+    entity_manager.add_component_with::<Velocity, _>(e2, |velocity| velocity.x = 2);
+
+    let mut query = Query::new();
+    query.check_component_or::<Velocity, _>(|velocity| velocity.x < 5);
+    assert!(query.is_volatile());
+    assert_eq!(entity_manager.collect(&query), [e2, e3]);
+}
+
+#[test]
+fn test_entity_manager_42() {
+    let mut entity_manager = MyEntityManager::new();
+    let e = entity_manager.create_entity();
+    entity_manager.add_component_mut::<Position>(e);
+    entity_manager.add_component_with_mut::<Velocity, _>(e, |velocity| velocity.y = 2);
+    assert!(entity_manager.has_component::<Position>(e));
+    assert_eq!(entity_manager.get_component::<Velocity>(e).y, 2);
+
+    entity_manager.remove_component_mut::<Position>(e);
+    assert!(!entity_manager.has_component::<Position>(e));
+    assert_eq!(
+        entity_manager
+            .iter_archetype::<(Velocity,)>()
+            .collect::<Vec<_>>(),
+        [e]
+    );
+}
+
+#[test]
+fn test_entity_manager_43() {
+    let changes = Rc::new(RefCell::new(Vec::new()));
+    let mut entity_manager = MyEntityManager::new();
+    let changes_ref = changes.clone();
+    entity_manager.on_change::<Position, _>(move |entity_manager, entity| {
+        let position = entity_manager.get_component::<Position>(entity);
+        changes_ref.borrow_mut().push((entity, position.x));
+    });
+    let e = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e, |position| position.x = 1);
+    entity_manager.add_component::<Velocity>(e);
+    entity_manager.get_component_mut::<Position>(e).x = 2;
+    entity_manager.update_component_with::<Velocity, _>(e, |velocity| velocity.x = 1);
+    assert!(changes.borrow().is_empty());
+
+    entity_manager.update_component_with::<Position, _>(e, |position| position.x += 1);
+    assert_eq!(*changes.borrow(), [(e, 3)]);
+}
+
+#[test]
+fn test_entity_manager_44() {
+    let mut world = MyEntityManager::new();
+    let w1 = world.create_entity();
+    world.add_component_with::<Position, _>(w1, |position| position.x = 1);
+
+    let mut prefab = MyEntityManager::new();
+    let p1 = prefab.create_entity();
+    let p2 = prefab.create_entity();
+    let p3 = prefab.create_entity();
+    prefab.add_component_with::<Position, _>(p1, |position| position.x = 2);
+    prefab.add_component_with::<Health, _>(p1, |health| health.value = 10);
+    prefab.add_component_with::<Velocity, _>(p3, |velocity| velocity.y = 3);
+    prefab.delete_entity(p2);
+
+    let mapping = world.merge_from(prefab);
+    assert_eq!(mapping.len(), 2);
+    assert_eq!(world.iter_all().count(), 3);
+    let n1 = mapping[&p1];
+    let n3 = mapping[&p3];
+    assert_ne!(n1, w1);
+    assert_eq!(world.get_component::<Position>(w1).x, 1);
+    assert_eq!(world.get_component::<Position>(n1).x, 2);
+    assert_eq!(world.get_component::<Health>(n1).value, 10);
+    assert_eq!(world.get_component::<Velocity>(n3).y, 3);
+    assert!(!world.has_component::<Position>(n3));
+    assert_eq!(
+        world
+            .iter_archetype::<(Position, Health)>()
+            .collect::<Vec<_>>(),
+        [n1]
+    );
+}
+
+#[test]
+fn test_entity_manager_45() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    let e3 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e2);
+    assert_eq!(entity_manager.orphans(), [e1, e3]);
+
+    entity_manager.add_component::<Health>(e3);
+    entity_manager.add_component::<Velocity>(e3);
+    entity_manager.remove_component::<Position>(e2);
+    assert_eq!(entity_manager.orphans(), [e1, e2]);
+}
+
+#[test]
+fn test_entity_manager_46() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Health, _>(e1, |health| health.value = 0);
+    entity_manager.add_component_with::<Health, _>(e2, |health| health.value = 5);
+    entity_manager.add_component::<Velocity>(e2);
+
+    let mut buffer = CommandBuffer::new();
+    for entity in entity_manager.iter(Query::new().check_component::<Health>()) {
+        if entity_manager.get_component::<Health>(entity).value == 0 {
+            buffer.delete(entity);
+            buffer.create(|entity_manager, entity| {
+                entity_manager.add_component_with::<Position, _>(entity, |position| position.x = 4);
+            });
+        } else {
+            buffer.remove_component::<Velocity>(entity);
+            buffer.add_component_with::<Position, _>(entity, |position| position.y = 2);
+        }
+    }
+    assert_eq!(buffer.len(), 4);
+    assert!(entity_manager.is_alive(e1));
+
+    entity_manager.apply(buffer);
+    assert!(!entity_manager.has_component::<Velocity>(e2));
+    assert_eq!(entity_manager.get_component::<Position>(e2).y, 2);
+    // id of deleted e1 is re-used by the created entity
+    assert_eq!(entity_manager.iter_all().count(), 2);
+    assert!(!entity_manager.has_component::<Health>(e1));
+    assert_eq!(entity_manager.get_component::<Position>(e1).x, 4);
+    assert_eq!(entity_manager.iter_archetype::<(Position,)>().count(), 2);
+}
+
+#[test]
+fn test_entity_manager_47() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    let e3 = entity_manager.create_entity();
+    entity_manager.add_component::<Health>(e1);
+    entity_manager.add_component::<Health>(e2);
+    entity_manager.add_component::<Position>(e2);
+    entity_manager.add_component::<Position>(e3);
+
+    let tick = entity_manager.advance_tick();
+    entity_manager.get_component_mut::<Health>(e2).value = 3;
+
+    let mut query = Query::new();
+    query.check_changed::<Health>(tick);
+    assert!(query.is_volatile());
+    assert_eq!(entity_manager.collect(&query), [e2]);
+    query.check_component::<Position>();
+    assert_eq!(entity_manager.collect(&query), [e2]);
+
+    let mut query = Query::new();
+    query.check_changed::<Health>(0);
+    assert_eq!(entity_manager.collect(&query), [e1, e2]);
+}
+
+#[test]
+fn test_entity_manager_48() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e1, |position| position.x = 7);
+
+    let position = entity_manager.get_component_cloned::<Position>(e1);
+    // no borrow is held, so structural changes are allowed
+    entity_manager.add_component::<Position>(e2);
+    entity_manager.get_component_mut::<Position>(e1).x = 8;
+    assert_eq!(position.x, 7);
+
+    assert_eq!(
+        entity_manager
+            .try_get_component_cloned::<Position>(e1)
+            .map(|position| position.x),
+        Some(8)
+    );
+    entity_manager.remove_component::<Position>(e1);
+    assert!(entity_manager
+        .try_get_component_cloned::<Position>(e1)
+        .is_none());
+}
+
+#[test]
+fn test_entity_manager_49() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    let e3 = entity_manager.create_entity();
+    let e4 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e1);
+    entity_manager.add_component::<Position>(e2);
+    entity_manager.add_component::<Health>(e2);
+    entity_manager.add_component::<Health>(e3);
+    entity_manager.add_component::<Velocity>(e4);
+
+    let positions = Query::new().with_component::<Position>();
+    let healths = Query::new().with_component::<Health>();
+    let pairs: Vec<_> = entity_manager.iter_join(&positions, &healths).collect();
+    assert_eq!(pairs, [(e1, e2), (e1, e3), (e2, e2), (e2, e3)]);
+
+    let velocities = Query::new().with_component::<Velocity>();
+    assert_eq!(
+        entity_manager
+            .iter_join(&velocities, &positions)
+            .collect::<Vec<_>>(),
+        [(e4, e1), (e4, e2)]
+    );
+    assert_eq!(
+        entity_manager
+            .iter_join(&velocities, &velocities.clone().with_component::<Health>())
+            .count(),
+        0
+    );
+}
+
+#[test]
+#[should_panic(expected = "entity 0 is not alive")]
+fn test_entity_manager_50() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e1);
+    entity_manager.delete_entity(e1);
+    entity_manager.get_component::<Position>(e1);
+}
+
+#[test]
+fn test_entity_manager_51() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    let e3 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e1, |position| position.x = 1);
+    entity_manager.add_component_with::<Position, _>(e2, |position| position.x = 2);
+    entity_manager.add_component_with::<Velocity, _>(e2, |velocity| velocity.x = 3);
+    entity_manager.add_component::<Velocity>(e3);
+
+    let items: Vec<_> = entity_manager
+        .iter_with::<(Position, Opt<Velocity>)>()
+        .map(|(entity, (position, velocity))| {
+            (entity, position.x, velocity.map(|velocity| velocity.x))
+        })
+        .collect();
+    assert_eq!(items, [(e1, 1, None), (e2, 2, Some(3))]);
+
+    let (position, velocity) = entity_manager.components_of::<(Opt<Position>, Velocity)>(e3);
+    assert!(position.is_none());
+    assert_eq!(velocity.x, 0);
+    drop((position, velocity));
+
+    assert_eq!(
+        entity_manager
+            .iter_archetype::<(Opt<Position>, Velocity)>()
+            .count(),
+        2
+    );
+    assert_eq!(entity_manager.iter_with::<(Opt<Health>,)>().count(), 3);
+}
+
+#[test]
+fn test_entity_manager_52() {
+    assert_eq!(
+        MyEntityManager::schema(),
+        ["Position", "Velocity", "Health"]
+    );
+    assert_eq!(
+        EntityManager::<GaugeEMC>::schema(),
+        ["Position", "Gauge<Health>", "Gauge<Mana>"]
+    );
+}
+
+#[derive(Default, Clone, PartialEq)]
+struct Score(u32);
+
+impl Component for Score {
+    type Storage = BasicVecStorage<Score>;
+}
+
+#[derive(Default, Clone, PartialEq)]
+struct Team(u8);
+
+impl Component for Team {
+    type Storage = BasicVecStorage<Team>;
+}
+
+create_entity_manager_component!(DiffEMC { Score, Team });
+
+#[test]
+fn test_entity_manager_53() {
+    let snapshot = || {
+        let mut entity_manager = EntityManager::<DiffEMC>::new();
+        for id in 0..4 {
+            let entity = entity_manager.create_entity();
+            entity_manager.add_component_with::<Score, _>(entity, |score| score.0 = id);
+        }
+        entity_manager.add_component::<Team>(Entity::new(1));
+        entity_manager
+    };
+    let mut before = snapshot();
+    let mut after = snapshot();
+    assert!(before.diff::<(Score, Team)>(&after).is_empty());
+
+    after.get_component_mut::<Score>(Entity::new(0)).0 = 10;
+    after.remove_component::<Team>(Entity::new(1));
+    after.add_component_with::<Team, _>(Entity::new(2), |team| team.0 = 2);
+    let e4 = after.create_entity();
+    after.delete_entity(Entity::new(3));
+    after.add_component_with::<Score, _>(e4, |score| score.0 = 4);
+
+    let diff = before.diff::<(Score, Team)>(&after);
+    assert_eq!(diff.created(), [e4]);
+    assert_eq!(diff.destroyed(), [Entity::new(3)]);
+    let changes: Vec<_> = diff
+        .components()
+        .iter()
+        .map(|(entity, name, change)| (entity.id, name.rsplit("::").next().unwrap(), *change))
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            (0, "Score", ComponentChange::Changed),
+            (3, "Score", ComponentChange::Removed),
+            (4, "Score", ComponentChange::Added),
+            (1, "Team", ComponentChange::Removed),
+            (2, "Team", ComponentChange::Added),
+        ]
+    );
+
+    before.apply_diff(diff);
+    assert!(before.diff::<(Score, Team)>(&after).is_empty());
+    assert!(!before.is_alive(Entity::new(3)));
+    assert_eq!(before.get_component::<Score>(e4).0, 4);
+    assert_eq!(before.get_component::<Team>(Entity::new(2)).0, 2);
+    assert_eq!(
+        before.iter_archetype::<(Score, Team)>().collect::<Vec<_>>(),
+        [Entity::new(2)]
+    );
+}
+
+create_entity_manager_component!(OverrideEMC {
+    Position => entity_system::TrackedVecStorage,
+    Velocity,
+    Gauge<Mana> => TrackedVecStorage
+});
+
+#[test]
+fn test_entity_manager_54() {
+    let mut entity_manager = EntityManager::<OverrideEMC>::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e1);
+    entity_manager.add_component::<Position>(e2);
+    entity_manager.add_component::<Gauge<Mana>>(e2);
+    entity_manager.add_component::<Velocity>(e2);
+
+    let tick = entity_manager.advance_tick();
+    entity_manager.get_component_mut::<Position>(e2).x = 4;
+    let changed: Vec<_> = entity_manager
+        .iter_changed::<Position>(tick)
+        .map(|(entity, position)| (entity, position.x))
+        .collect();
+    assert_eq!(changed, [(e2, 4)]);
+    assert_eq!(entity_manager.iter_changed::<Gauge<Mana>>(tick).count(), 0);
+    assert_eq!(
+        EntityManager::<OverrideEMC>::schema(),
+        ["Position", "Velocity", "Gauge<Mana>"]
+    );
+}
+
+#[test]
+fn test_entity_manager_55() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    let e3 = entity_manager.spawn_pooled();
+    entity_manager.add_component::<Position>(e3);
+    entity_manager.add_component::<Position>(e2);
+    entity_manager.add_component::<Velocity>(e1);
+    assert_eq!(entity_manager.entities_with::<Position>(), [e2, e3]);
+
+    entity_manager.despawn_pooled(e3);
+    assert_eq!(entity_manager.entities_with::<Position>(), [e2]);
+
+    for entity in entity_manager.iter_entities_with::<Velocity>() {
+        entity_manager.add_component::<Position>(entity);
+    }
+    assert_eq!(entity_manager.entities_with::<Position>(), [e1, e2]);
+    assert!(entity_manager.entities_with::<Health>().is_empty());
+}
+
+#[test]
+fn test_entity_manager_56() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    let e3 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e1, |position| position.x = 1);
+    entity_manager.add_component_with::<Position, _>(e2, |position| position.x = 2);
+    entity_manager.add_component::<Velocity>(e2);
+    entity_manager.add_component::<Velocity>(e3);
+
+    let positions: Vec<_> = entity_manager
+        .collect_component::<Position>(&Query::new())
+        .into_iter()
+        .map(|(entity, position)| (entity, position.x))
+        .collect();
+    assert_eq!(positions, [(e1, 1), (e2, 2)]);
+
+    let moving =
+        entity_manager.collect_component::<Position>(&Query::new().with_component::<Velocity>());
+    entity_manager.remove_component::<Position>(e2);
+    assert_eq!(moving.len(), 1);
+    assert_eq!(moving[0].0, e2);
+    assert_eq!(moving[0].1.x, 2);
+}
+
+#[test]
+fn test_entity_manager_57() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e1);
+    entity_manager.add_component::<Velocity>(e2);
+
+    let mut query = Query::new();
+    assert!(query.is_empty());
+    query
+        .check_component::<Position>()
+        .check_component_by::<Position, _>(|position| position.x == 0);
+    assert_eq!(query.len(), 2);
+    assert!(query.is_volatile());
+    assert_eq!(entity_manager.collect(&query), [e1]);
+
+    query.clear().check_component::<Velocity>();
+    assert_eq!(query.len(), 1);
+    assert!(!query.is_volatile());
+    assert_eq!(entity_manager.collect(&query), [e2]);
+
+    query.clear();
+    assert_eq!(entity_manager.collect(&query), [e1, e2]);
+}
+
+#[test]
+fn test_entity_manager_58() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Velocity, _>(e1, |velocity| velocity.x = 3);
+
+    assert_eq!(entity_manager.get_component_or::<Velocity>(e1).x, 3);
+    assert_eq!(entity_manager.get_component_or::<Velocity>(e2).x, 0);
+
+    entity_manager.delete_entity(e1);
+    assert_eq!(entity_manager.get_component_or::<Velocity>(e1).x, 0);
+}
+
+#[test]
+fn test_entity_manager_59() {
+    let mut entity_manager = MyEntityManager::new();
+    let entities: Vec<_> = (0..4).map(|_| entity_manager.create_entity()).collect();
+    entity_manager.delete_entity(entities[1]);
+    entity_manager.delete_entity(entities[3]);
+    assert_eq!(entity_manager.live_count(), 2);
+    assert_eq!(entity_manager.high_water(), 4);
+
+    entity_manager.compact();
+    assert_eq!(entity_manager.live_count(), 2);
+    assert_eq!(entity_manager.high_water(), 3);
+}
+
+#[test]
+fn test_entity_manager_60() {
+    let deleted = Rc::new(RefCell::new(Vec::new()));
+    let mut entity_manager = MyEntityManager::new();
+    let deleted_clone = deleted.clone();
+    entity_manager.set_on_delete(move |entity| deleted_clone.borrow_mut().push(entity));
+    let e1 = entity_manager.create_entity();
+
+    let mut buffer = CommandBuffer::new();
+    buffer.delete(e1);
+    buffer.delete(e1);
+    entity_manager.apply(buffer);
+    assert_eq!(*deleted.borrow(), [e1]);
+
+    let generation = entity_manager.generation();
+    entity_manager.delete_entity(e1);
+    entity_manager.delete_entity(Entity::new(10));
+    assert_eq!(entity_manager.generation(), generation);
+    assert_eq!(*deleted.borrow(), [e1]);
+    assert_eq!(entity_manager.create_entity(), e1);
+}
+
+#[test]
+fn test_entity_manager_61() {
+    let mut entity_manager = MyEntityManager::with_capacity_limit(1);
+    let e1 = entity_manager.spawn_pooled();
+    entity_manager.despawn_pooled(e1);
+    assert!(entity_manager.try_create_entity().is_none());
+    assert_eq!(entity_manager.spawn_pooled(), e1);
+    assert_eq!(entity_manager.live_count(), 1);
+
+    let deleted = Rc::new(RefCell::new(Vec::new()));
+    let mut entity_manager = MyEntityManager::new();
+    let deleted_clone = deleted.clone();
+    entity_manager.set_on_delete(move |entity| deleted_clone.borrow_mut().push(entity));
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e1, |position| position.x = 5);
+    entity_manager.add_component::<Velocity>(e1);
+    entity_manager.add_component::<Position>(e2);
+
+    entity_manager.despawn_pooled(e1);
+    assert_eq!(
+        entity_manager.stats().components,
+        [("Position", 1), ("Velocity", 0), ("Health", 0)]
+    );
+    entity_manager.remove_component_all::<Position>();
+    assert!(!entity_manager.has_component::<Position>(e2));
+
+    assert_eq!(entity_manager.spawn_pooled(), e1);
+    assert_eq!(entity_manager.get_component::<Position>(e1).x, 5);
+    let positions: Vec<_> = entity_manager.iter_archetype::<(Position,)>().collect();
+    assert_eq!(positions, [e1]);
+
+    entity_manager.despawn_pooled(e1);
+    entity_manager.delete_entity(e1);
+    assert_eq!(*deleted.borrow(), [e1, e1]);
+    entity_manager.spawn_pooled();
+    assert!(!entity_manager.has_component::<Position>(e1));
+}
+
+#[derive(Default)]
+struct ManualEMC {
+    positions: RefCell<BasicVecStorage<Position>>,
+}
+
+impl entity_system::EntityManagerComponent for ManualEMC {
+    fn free(&mut self, entity: Entity) {
+        self.positions.get_mut().free(entity);
+    }
+
+    fn component_count(&self, entity: Entity) -> usize {
+        self.positions.borrow().has(entity) as usize
+    }
+
+    fn reset(&mut self, entity: Entity) {
+        let positions = self.positions.get_mut();
+        if positions.has(entity) {
+            *positions.get_mut(entity) = Position::default();
+        }
+    }
+}
+
+impl entity_system::StorageAccess<Position> for ManualEMC {
+    type Storage = BasicVecStorage<Position>;
+
+    fn get(&self) -> std::cell::Ref<'_, Self::Storage> {
+        self.positions.borrow()
+    }
+
+    fn get_mut(&self) -> std::cell::RefMut<'_, Self::Storage> {
+        self.positions.borrow_mut()
+    }
+}
+
+#[test]
+fn test_entity_manager_62() {
+    let mut entity_manager = EntityManager::<ManualEMC>::new();
+    let e1 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e1, |position| position.x = 3);
+    assert_eq!(entity_manager.get_component::<Position>(e1).x, 3);
+    assert!(entity_manager.stats().components.is_empty());
+    assert!(EntityManager::<ManualEMC>::schema().is_empty());
+    entity_manager.compact();
+
+    entity_manager.delete_entity(e1);
+    assert!(!entity_manager.storage::<Position>().has(e1));
+}
+
+#[test]
+#[should_panic(expected = "entity 0 is not alive")]
+fn test_entity_manager_63() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e1);
+    entity_manager.delete_entity(e1);
+    entity_manager.get_component_cloned::<Position>(e1);
+}
+
+#[test]
+fn test_entity_manager_64() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    entity_manager.add_component_with::<Position, _>(e1, |position| position.x = 1);
+    assert_eq!(
+        entity_manager
+            .try_get_component_cloned::<Position>(e1)
+            .map(|position| position.x),
+        Some(1)
+    );
+
+    entity_manager.despawn_pooled(e1);
+    assert!(entity_manager
+        .try_get_component_cloned::<Position>(e1)
+        .is_none());
+}
+
+#[test]
+fn test_entity_manager_65() {
+    let mut entity_manager = MyEntityManager::new();
+    let e1 = entity_manager.create_entity();
+    let e2 = entity_manager.create_entity();
+    entity_manager.add_component::<Position>(e1);
+    entity_manager.add_component::<Position>(e2);
+
+    let generation = entity_manager.generation();
+    entity_manager
+        .add_components_from([(e1, Position { x: 1, y: 2 }), (e2, Position { x: 3, y: 4 })]);
+    assert_eq!(entity_manager.generation(), generation);
+    assert_eq!(entity_manager.get_component::<Position>(e2).x, 3);
+
+    let e3 = entity_manager.create_entity();
+    let generation = entity_manager.generation();
+    entity_manager
+        .add_components_from([(e1, Position { x: 5, y: 6 }), (e3, Position { x: 7, y: 8 })]);
+    assert_ne!(entity_manager.generation(), generation);
+    assert_eq!(entity_manager.get_component::<Position>(e1).x, 5);
+    assert_eq!(entity_manager.iter_archetype::<(Position,)>().count(), 3);
+}
@@ -1,6 +1,10 @@
-use entity_system::{create_event_adapters, Connection, EventDispatcher, EventHandler};
+use entity_system::{
+    create_event_adapters, Connection, ContextEventHandler, EventDispatcher, EventHandler,
+};
 
+#[derive(Clone)]
 struct Event1(i32);
+#[derive(Clone)]
 struct Event2(i32);
 create_event_adapters!(MyEventAdapters1 { Event1, Event2 });
 
@@ -140,3 +144,395 @@ fn test_event_dispatcher_04() {
     assert_eq!(receiver.borrow().event1, 1);
     assert_eq!(receiver.borrow().event2, 1);
 }
+
+#[test]
+fn test_event_dispatcher_05() {
+    let dispatcher = MyDispatcher1::new();
+    let receiver = Receiver1::new();
+    let connection1 = dispatcher.create_connection::<Receiver1, Event1>(&receiver);
+    let connection2 = connection1.clone();
+    connection1.connect();
+    dispatcher.push(Event1 { 0: 0 });
+    dispatcher.dispatch();
+    assert_eq!(receiver.borrow().event1, 1);
+    connection2.disconnect();
+    dispatcher.push(Event1 { 0: 0 });
+    dispatcher.dispatch();
+    assert_eq!(receiver.borrow().event1, 1);
+}
+
+#[test]
+fn test_event_dispatcher_06() {
+    let dispatcher = MyDispatcher1::new();
+    let receiver = Receiver1::new();
+    let connection = dispatcher.create_connection::<Receiver1, Event1>(&receiver);
+    assert!(!dispatcher.is_connected::<Receiver1, Event1>(&receiver));
+    connection.connect();
+    dispatcher.dispatch();
+    assert!(dispatcher.is_connected::<Receiver1, Event1>(&receiver));
+    assert!(!dispatcher.is_connected::<Receiver1, Event2>(&receiver));
+    connection.disconnect();
+    dispatcher.dispatch();
+    assert!(!dispatcher.is_connected::<Receiver1, Event1>(&receiver));
+}
+
+#[test]
+fn test_event_dispatcher_07() {
+    let dispatcher = MyDispatcher1::new();
+    let receiver1 = Receiver1::new();
+    let receiver2 = Receiver1::new();
+    dispatcher
+        .create_connection::<Receiver1, Event1>(&receiver1)
+        .connect();
+    dispatcher
+        .create_connection::<Receiver1, Event1>(&receiver2)
+        .connect();
+    dispatcher
+        .create_connection::<Receiver1, Event2>(&receiver1)
+        .connect();
+    dispatcher.disconnect_all::<Event1>();
+    dispatcher.push(Event1 { 0: 0 });
+    dispatcher.push(Event2 { 0: 0 });
+    dispatcher.dispatch();
+    assert_eq!(receiver1.borrow().event1, 0);
+    assert_eq!(receiver2.borrow().event1, 0);
+    assert_eq!(receiver1.borrow().event2, 1);
+}
+
+struct Context {
+    value: i32,
+}
+create_event_adapters!(MyEventAdapters2: Context { Event1 });
+type MyDispatcher2 = EventDispatcher<MyEventAdapters2, Context>;
+
+struct Receiver3 {
+    sum: i32,
+}
+
+impl ContextEventHandler<Event1, Context> for Receiver3 {
+    fn on_event_with(&mut self, event: &Event1, context: &Context) {
+        self.sum += event.0 * context.value;
+    }
+}
+
+#[test]
+fn test_event_dispatcher_08() {
+    let dispatcher = MyDispatcher2::with_context(Context { value: 3 });
+    let receiver = Rc::new(RefCell::new(Receiver3 { sum: 0 }));
+    let connection = dispatcher.create_connection::<Receiver3, Event1>(&receiver);
+    connection.connect();
+    dispatcher.push(Event1 { 0: 2 });
+    dispatcher.dispatch();
+    assert_eq!(receiver.borrow().sum, 6);
+    assert_eq!(dispatcher.context().value, 3);
+    assert!(dispatcher.is_connected::<Receiver3, Event1>(&receiver));
+}
+
+#[test]
+fn test_event_dispatcher_09() {
+    let dispatcher = MyDispatcher1::new();
+    let count = Rc::new(RefCell::new(0));
+    let count_ref = count.clone();
+    let connection = dispatcher.connect_fn(move |event: &Event1| {
+        *count_ref.borrow_mut() += event.0;
+    });
+
+    dispatcher.push(Event1(2));
+    dispatcher.push(Event1(3));
+    dispatcher.dispatch();
+    assert_eq!(*count.borrow(), 5);
+
+    connection.disconnect();
+    dispatcher.push(Event1(4));
+    dispatcher.dispatch();
+    assert_eq!(*count.borrow(), 5);
+}
+
+#[test]
+fn test_event_dispatcher_10() {
+    let dispatcher1 = MyDispatcher1::new();
+    let dispatcher2 = MyDispatcher1::new();
+    assert_ne!(dispatcher1.id(), dispatcher2.id());
+
+    let receiver = Receiver1::new();
+    let connection: MyConnection1<_, Event1> = dispatcher2.create_connection(&receiver);
+
+    connection.connect();
+    dispatcher1.push(Event1(0));
+    dispatcher1.dispatch();
+    dispatcher2.push(Event1(0));
+    dispatcher2.dispatch();
+    assert_eq!(receiver.borrow().event1, 1);
+}
+
+#[test]
+fn test_event_dispatcher_11() {
+    let dispatcher = MyDispatcher1::new();
+    let receiver = Receiver1::new();
+    let connection: MyConnection1<_, Event1> = dispatcher.create_connection(&receiver);
+    connection.connect();
+
+    let token1 = dispatcher.push(Event1(1));
+    let token2 = dispatcher.push(Event1(2));
+    assert_ne!(token1, token2);
+    dispatcher.push(Event1(3));
+    assert!(dispatcher.cancel(token1));
+    assert!(!dispatcher.cancel(token1));
+    assert!(dispatcher.cancel_last());
+    dispatcher.dispatch();
+    assert_eq!(receiver.borrow().event1, 1);
+
+    assert!(!dispatcher.cancel(token2));
+    assert!(!dispatcher.cancel_last());
+    connection.disconnect();
+    assert!(!dispatcher.cancel_last());
+}
+
+#[test]
+fn test_event_dispatcher_12() {
+    use entity_system::Adapter;
+
+    let receiver1 = Receiver1::new();
+    let receiver2 = Receiver1::new();
+    let mut adapter: Adapter<Event1> = Adapter::new();
+    adapter.connect(receiver1.clone());
+    adapter.connect(receiver2.clone());
+    adapter.invoke(&Event1(0), &());
+    adapter.disconnect(receiver1.clone());
+    adapter.invoke(&Event1(0), &());
+    assert_eq!(receiver1.borrow().event1, 1);
+    assert_eq!(receiver2.borrow().event1, 2);
+
+    let dispatcher = MyDispatcher1::new();
+    let count = Rc::new(RefCell::new(0));
+    let count_ref = count.clone();
+    let weak_dispatcher = Rc::downgrade(&dispatcher);
+    let _connection = dispatcher.connect_fn(move |_: &Event1| {
+        let count_ref = count_ref.clone();
+        let dispatcher = weak_dispatcher.upgrade().unwrap();
+        dispatcher.connect_fn(move |_: &Event1| *count_ref.borrow_mut() += 1);
+    });
+    dispatcher.dispatch();
+    dispatcher.push(Event1(0));
+    dispatcher.push(Event1(0));
+    dispatcher.dispatch();
+    assert_eq!(*count.borrow(), 0);
+
+    dispatcher.push(Event1(0));
+    dispatcher.dispatch();
+    assert_eq!(*count.borrow(), 2);
+}
+
+#[test]
+fn test_event_dispatcher_13() {
+    let dispatcher = MyDispatcher1::new();
+    let values = Rc::new(RefCell::new(Vec::new()));
+    let values_ref = values.clone();
+    let _connection =
+        dispatcher.connect_fn(move |event: &Event1| values_ref.borrow_mut().push(event.0));
+    dispatcher.dispatch();
+    assert!(!dispatcher.dispatch_one());
+
+    dispatcher.push(Event1(1));
+    dispatcher.push(Event1(2));
+    assert!(dispatcher.dispatch_one());
+    assert_eq!(*values.borrow(), [1]);
+    assert!(dispatcher.dispatch_one());
+    assert_eq!(*values.borrow(), [1, 2]);
+    assert!(!dispatcher.dispatch_one());
+}
+
+#[test]
+fn test_event_dispatcher_14() {
+    let dispatcher = MyDispatcher1::new();
+    let receiver = Receiver1::new();
+    let (connection1, _connection2) =
+        entity_system::connect_all!(dispatcher, receiver, [Event1, Event2]);
+    dispatcher.push(Event1(0));
+    dispatcher.push(Event2(0));
+    dispatcher.dispatch();
+    assert_eq!(receiver.borrow().event1, 1);
+    assert_eq!(receiver.borrow().event2, 1);
+
+    connection1.disconnect();
+    dispatcher.push(Event1(0));
+    dispatcher.push(Event2(0));
+    dispatcher.dispatch();
+    assert_eq!(receiver.borrow().event1, 1);
+    assert_eq!(receiver.borrow().event2, 2);
+}
+
+#[test]
+fn test_event_dispatcher_15() {
+    let dispatcher = MyDispatcher1::new();
+    dispatcher.start_recording::<Event1>();
+    dispatcher.start_recording::<Event2>();
+    dispatcher.push(Event1(1));
+    dispatcher.push(Event2(2));
+    dispatcher.push(Event1(3));
+    dispatcher.stop_recording::<Event2>();
+    dispatcher.push(Event2(4));
+    dispatcher.dispatch();
+    let log = dispatcher.take_recording();
+    assert_eq!(log.len(), 3);
+    assert!(dispatcher.take_recording().is_empty());
+
+    let replayed = MyDispatcher1::new();
+    let values = Rc::new(RefCell::new(Vec::new()));
+    let values1 = values.clone();
+    let _connection1 =
+        replayed.connect_fn(move |event: &Event1| values1.borrow_mut().push(event.0));
+    let values2 = values.clone();
+    let _connection2 =
+        replayed.connect_fn(move |event: &Event2| values2.borrow_mut().push(event.0));
+    replayed.dispatch();
+    replayed.replay(&log);
+    replayed.replay(&log);
+    replayed.dispatch();
+    assert_eq!(*values.borrow(), [1, 2, 3, 1, 2, 3]);
+}
+
+#[test]
+fn test_event_dispatcher_16() {
+    let dispatcher = MyDispatcher1::new();
+    let receiver1 = Receiver1::new();
+    let receiver2 = Receiver1::new();
+    entity_system::connect_all!(dispatcher, receiver1, [Event1]);
+    entity_system::connect_all!(dispatcher, receiver2, [Event1]);
+    dispatcher.push(Event1 { 0: 0 });
+    dispatcher.dispatch();
+    assert_eq!(receiver1.borrow().event1, 1);
+    assert_eq!(receiver2.borrow().event1, 1);
+
+    let weak = Rc::downgrade(&receiver1);
+    drop(receiver1);
+    assert!(weak.upgrade().is_none());
+    dispatcher.push(Event1 { 0: 0 });
+    dispatcher.dispatch();
+    assert_eq!(receiver2.borrow().event1, 2);
+}
+
+#[test]
+fn test_event_dispatcher_17() {
+    let dispatcher = MyDispatcher1::new();
+    let values = Rc::new(RefCell::new(Vec::new()));
+    let values_ref = values.clone();
+    let _connection =
+        dispatcher.connect_fn(move |event: &Event1| values_ref.borrow_mut().push(event.0));
+    assert_eq!(dispatcher.pending_count(), 1);
+    dispatcher.dispatch();
+    assert_eq!(dispatcher.pending_count(), 0);
+
+    for value in 0..5 {
+        dispatcher.push(Event1(value));
+    }
+    assert_eq!(dispatcher.pending_count(), 5);
+    assert_eq!(dispatcher.dispatch_up_to(3), 3);
+    assert_eq!(*values.borrow(), [0, 1, 2]);
+    assert_eq!(dispatcher.pending_count(), 2);
+    assert_eq!(dispatcher.dispatch_up_to(0), 0);
+    assert_eq!(dispatcher.dispatch_up_to(10), 2);
+    assert_eq!(*values.borrow(), [0, 1, 2, 3, 4]);
+    assert_eq!(dispatcher.pending_count(), 0);
+}
+
+#[test]
+fn test_event_dispatcher_18() {
+    let dispatcher = MyDispatcher1::new();
+    let filtered = Receiver1::new();
+    let all = Receiver1::new();
+    let connection = dispatcher.connect_filtered(&filtered, |event: &Event1| event.0 > 10);
+    dispatcher.create_connection::<_, Event1>(&all).connect();
+    dispatcher.dispatch();
+    assert!(dispatcher.is_connected::<_, Event1>(&filtered));
+
+    for value in [5, 15, 20, 10] {
+        dispatcher.push(Event1(value));
+    }
+    dispatcher.dispatch();
+    assert_eq!(filtered.borrow().event1, 2);
+    assert_eq!(all.borrow().event1, 4);
+
+    connection.disconnect();
+    dispatcher.push(Event1(30));
+    dispatcher.dispatch();
+    assert!(!dispatcher.is_connected::<_, Event1>(&filtered));
+    assert_eq!(filtered.borrow().event1, 2);
+    assert_eq!(all.borrow().event1, 5);
+}
+
+#[test]
+fn test_event_dispatcher_19() {
+    use entity_system::{Dispatcher, FnEventHandler};
+
+    let dispatcher = MyDispatcher1::new();
+    let count = Rc::new(RefCell::new(0));
+    let count_ref = count.clone();
+    dispatcher.connect::<_, Event1>(Rc::new(RefCell::new(FnEventHandler::new(
+        move |_: &Event1| *count_ref.borrow_mut() += 1,
+    ))));
+    dispatcher.push(Event1(0));
+    dispatcher.dispatch();
+    dispatcher.push(Event1(1));
+    dispatcher.dispatch();
+    assert_eq!(*count.borrow(), 2);
+
+    dispatcher.disconnect_all::<Event1>();
+    dispatcher.push(Event1(2));
+    dispatcher.dispatch();
+    assert_eq!(*count.borrow(), 2);
+}
+
+#[test]
+fn test_event_dispatcher_20() {
+    let dispatcher = MyDispatcher1::new();
+    let receiver1 = Receiver1::new();
+    let receiver2 = Receiver1::new();
+    let connection1 = dispatcher.create_connection::<_, Event1>(&receiver1);
+    let connection2 = dispatcher.create_connection::<_, Event1>(&receiver2);
+    connection1.connect();
+    let states = Rc::new(RefCell::new(Vec::new()));
+    let states_ref = states.clone();
+    let weak = Rc::downgrade(&dispatcher);
+    let (receiver1_ref, receiver2_ref) = (receiver1.clone(), receiver2.clone());
+    let _connection = dispatcher.connect_fn(move |_: &Event1| {
+        let dispatcher = weak.upgrade().unwrap();
+        states_ref.borrow_mut().push((
+            dispatcher.is_connected::<_, Event1>(&receiver1_ref),
+            dispatcher.is_connected::<_, Event1>(&receiver2_ref),
+        ));
+        connection1.disconnect();
+        connection2.connect();
+    });
+    dispatcher.push(Event1(0));
+    dispatcher.dispatch();
+    assert_eq!(receiver1.borrow().event1, 1);
+    assert_eq!(receiver2.borrow().event1, 0);
+
+    dispatcher.push(Event1(1));
+    dispatcher.dispatch();
+    assert_eq!(receiver1.borrow().event1, 1);
+    assert_eq!(receiver2.borrow().event1, 1);
+    assert_eq!(*states.borrow(), [(true, false), (false, true)]);
+}
+
+#[test]
+fn test_event_dispatcher_21() {
+    let dispatcher = MyDispatcher1::new();
+    let receiver = Receiver1::new();
+    let connection = dispatcher.create_connection::<_, Event1>(&receiver);
+    let weak = Rc::downgrade(&dispatcher);
+    let receiver_ref = receiver.clone();
+    let _connection = dispatcher.connect_fn(move |_: &Event1| {
+        let dispatcher = weak.upgrade().unwrap();
+        if !dispatcher.is_connected::<_, Event1>(&receiver_ref) {
+            connection.connect();
+        }
+    });
+    for value in 0..3 {
+        dispatcher.push(Event1(value));
+        dispatcher.dispatch();
+    }
+    assert!(dispatcher.is_connected::<_, Event1>(&receiver));
+    assert_eq!(receiver.borrow().event1, 2);
+}
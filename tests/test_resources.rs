@@ -0,0 +1,26 @@
+use entity_system::Resources;
+
+struct Level(u32);
+
+#[test]
+fn test_resources_01() {
+    let mut resources = Resources::new();
+    assert!(!resources.contains::<Level>());
+    assert!(resources.remove::<Level>().is_none());
+
+    resources.insert(Level(1));
+    resources.insert(Level(2));
+    resources.insert("name");
+    assert_eq!(resources.get::<Level>().unwrap().0, 2);
+    assert_eq!(*resources.get::<&str>().unwrap(), "name");
+
+    {
+        let _level = resources.get::<Level>().unwrap();
+        assert_eq!(resources.get::<Level>().unwrap().0, 2);
+    }
+    resources.get_mut::<Level>().unwrap().0 += 1;
+
+    assert_eq!(resources.remove::<Level>().unwrap().0, 3);
+    assert!(resources.get::<Level>().is_none());
+    assert!(resources.contains::<&str>());
+}
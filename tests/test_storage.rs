@@ -0,0 +1,311 @@
+use entity_system::{
+    BasicVecStorage, Entity, SortKey, SortedStorage, Storage, StorageError, TrackedStorage,
+    TrackedVecStorage,
+};
+
+#[test]
+fn test_storage_01() {
+    let mut storage: BasicVecStorage<u32> = Default::default();
+    let e1 = Entity::new(1);
+    let e2 = Entity::new(5);
+    assert!(storage.try_get(e1).is_none());
+    assert!(storage.try_get_mut(e2).is_none());
+
+    storage.alloc(e1);
+    *storage.try_get_mut(e1).unwrap() = 3;
+    assert_eq!(storage.try_get(e1), Some(&3));
+    assert!(storage.try_get(Entity::new(0)).is_none());
+    assert!(storage.try_get(e2).is_none());
+
+    storage.free(e1);
+    assert!(storage.try_get(e1).is_none());
+}
+
+#[test]
+fn test_storage_02() {
+    let mut storage: TrackedVecStorage<u32> = Default::default();
+    let e1 = Entity::new(1);
+    storage.alloc(e1);
+    storage.set_tick(1);
+    assert!(!storage.changed_since(e1, 1));
+    assert_eq!(storage.try_get(e1), Some(&0));
+    assert!(!storage.changed_since(e1, 1));
+
+    *storage.try_get_mut(e1).unwrap() = 3;
+    assert!(storage.changed_since(e1, 1));
+    assert!(storage.try_get_mut(Entity::new(2)).is_none());
+}
+
+#[test]
+fn test_storage_03() {
+    let mut storage: BasicVecStorage<u32> = Default::default();
+    storage.alloc(Entity::new(1));
+    storage.alloc(Entity::new(3));
+    storage.alloc(Entity::new(4));
+    storage.free(Entity::new(3));
+
+    storage.for_each_mut(&mut |entity, value| *value = entity.id * 10);
+    let mut r = Vec::new();
+    storage.for_each(&mut |entity, value| r.push((entity.id, *value)));
+    assert_eq!(r, [(1, 10), (4, 40)]);
+}
+
+#[test]
+fn test_storage_04() {
+    let mut storage: TrackedVecStorage<u32> = Default::default();
+    storage.alloc(Entity::new(0));
+    storage.alloc(Entity::new(2));
+    storage.set_tick(1);
+
+    storage.for_each_mut(&mut |_, value| *value += 1);
+    assert!(storage.changed_since(Entity::new(0), 1));
+    assert!(storage.changed_since(Entity::new(2), 1));
+
+    let mut count = 0;
+    storage.for_each(&mut |_, value| count += *value);
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn test_storage_05() {
+    let mut storage: TrackedVecStorage<u32> = Default::default();
+    assert_eq!(storage.len(), 0);
+    storage.alloc(Entity::new(2));
+    storage.alloc(Entity::new(5));
+    assert_eq!(storage.len(), 2);
+    storage.free(Entity::new(2));
+    assert_eq!(storage.len(), 1);
+}
+
+#[test]
+fn test_storage_06() {
+    let mut storage: BasicVecStorage<u32> = Default::default();
+    assert!(storage.is_empty());
+    storage.alloc(Entity::new(3));
+    storage.alloc(Entity::new(3));
+    storage.alloc(Entity::new(1));
+    assert_eq!(storage.len(), 2);
+    assert!(!storage.is_empty());
+
+    storage.free(Entity::new(3));
+    storage.free(Entity::new(3));
+    storage.free(Entity::new(8));
+    assert_eq!(storage.len(), 1);
+    storage.free(Entity::new(1));
+    assert!(storage.is_empty());
+}
+
+#[test]
+fn test_storage_07() {
+    let mut storage: BasicVecStorage<u32> = Default::default();
+    storage.alloc(Entity::new(1));
+    storage.alloc(Entity::new(4));
+    storage.clear();
+    assert!(storage.is_empty());
+    assert!(!storage.has(Entity::new(4)));
+
+    storage.alloc(Entity::new(2));
+    assert_eq!(*storage.get(Entity::new(2)), 0);
+    assert_eq!(storage.len(), 1);
+}
+
+#[test]
+fn test_storage_08() {
+    let mut storage: TrackedVecStorage<u32> = Default::default();
+    let entity = Entity::new(3);
+    assert_eq!(storage.try_alloc(entity), Ok(()));
+    assert!(storage.has(entity));
+
+    // 256 KB per item, far more than the address space for the last id
+    let mut storage: BasicVecStorage<[[[u64; 32]; 32]; 32]> = Default::default();
+    let entity = Entity::new(u32::MAX - 1);
+    assert_eq!(
+        storage.try_alloc(entity),
+        Err(StorageError::OutOfMemory(entity))
+    );
+    assert!(!storage.has(entity));
+    assert_eq!(storage.len(), 0);
+}
+
+#[derive(Default)]
+struct Position {
+    x: i32,
+}
+
+impl SortKey<i32> for Position {
+    fn key(&self) -> i32 {
+        self.x
+    }
+}
+
+#[test]
+fn test_storage_09() {
+    let mut storage: SortedStorage<Position, i32> = Default::default();
+    for (id, x) in [5, -2, 8, 5, 0].iter().enumerate() {
+        let entity = Entity::new(id as u32);
+        storage.alloc(entity);
+        storage.get_mut(entity).x = *x;
+    }
+    let ids = |storage: &SortedStorage<Position, i32>, range: std::ops::Range<i32>| {
+        storage
+            .range(range)
+            .map(|(entity, _)| entity.id)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&storage, -10..10), [1, 4, 0, 3, 2]);
+    assert_eq!(ids(&storage, 0..6), [4, 0, 3]);
+    assert_eq!(storage.range(5..).count(), 3);
+    assert_eq!(storage.range(..=0).count(), 2);
+
+    storage.free(Entity::new(0));
+    storage.get_mut(Entity::new(2)).x = 1;
+    assert_eq!(ids(&storage, 0..6), [4, 2, 3]);
+
+    storage.for_each_mut(&mut |_, position| position.x = -position.x);
+    assert_eq!(ids(&storage, -10..10), [3, 2, 4, 1]);
+    assert_eq!(storage.len(), 4);
+
+    storage.clear();
+    assert_eq!(storage.range(..).count(), 0);
+}
+
+#[test]
+fn test_storage_10() {
+    let mut storage: TrackedVecStorage<u32> = Default::default();
+    for id in 0..5 {
+        storage.alloc(Entity::new(id));
+    }
+    storage.free(Entity::new(4));
+    storage.free(Entity::new(3));
+    storage.free(Entity::new(1));
+    storage.shrink_to_fit();
+    assert_eq!(storage.len(), 2);
+    assert!(storage.has(Entity::new(2)));
+    assert!(!storage.has(Entity::new(3)));
+
+    storage.alloc(Entity::new(4));
+    assert!(storage.changed_since(Entity::new(4), 0));
+    assert_eq!(storage.len(), 3);
+}
+
+#[test]
+fn test_storage_11() {
+    let mut storage: BasicVecStorage<u32> = Default::default();
+    storage.alloc(Entity::new(0));
+    storage.alloc(Entity::new(2));
+    *storage.get_mut(Entity::new(0)) = 5;
+    *storage.get_mut(Entity::new(2)) = 7;
+
+    let entities = [
+        Entity::new(2),
+        Entity::new(1),
+        Entity::new(0),
+        Entity::new(10),
+    ];
+    assert_eq!(
+        storage.get_many(&entities),
+        [Some(&7), None, Some(&5), None]
+    );
+    assert!(storage.get_many(&[]).is_empty());
+}
+
+#[test]
+fn test_storage_12() {
+    let mut storage: BasicVecStorage<u32> = Default::default();
+    for id in [0, 63, 64, 65, 130, 200] {
+        storage.alloc(Entity::new(id));
+        *storage.get_mut(Entity::new(id)) = id;
+    }
+    storage.free(Entity::new(64));
+    storage.free(Entity::new(200));
+    assert_eq!(storage.len(), 4);
+    assert!(storage.has(Entity::new(63)));
+    assert!(!storage.has(Entity::new(64)));
+    assert!(!storage.has(Entity::new(1000)));
+
+    let mut items = Vec::new();
+    storage.for_each(&mut |entity, value| items.push((entity.id, *value)));
+    assert_eq!(items, [(0, 0), (63, 63), (65, 65), (130, 130)]);
+
+    storage.shrink_to_fit();
+    assert!(storage.has(Entity::new(130)));
+    assert!(!storage.has(Entity::new(200)));
+    storage.alloc(Entity::new(200));
+    assert_eq!(*storage.get(Entity::new(200)), 0);
+    assert_eq!(storage.len(), 5);
+}
+
+#[derive(Default)]
+struct MapStorage {
+    items: std::collections::HashMap<Entity, u32>,
+}
+
+impl Storage<u32> for MapStorage {
+    fn alloc(&mut self, entity: Entity) {
+        self.items.insert(entity, 0);
+    }
+
+    fn free(&mut self, entity: Entity) {
+        self.items.remove(&entity);
+    }
+
+    fn get(&self, entity: Entity) -> &u32 {
+        &self.items[&entity]
+    }
+
+    fn get_mut(&mut self, entity: Entity) -> &mut u32 {
+        self.items.get_mut(&entity).unwrap()
+    }
+
+    fn swap(&mut self, a: Entity, b: Entity) {
+        let value_a = self.items[&a];
+        let value_b = std::mem::replace(self.items.get_mut(&b).unwrap(), value_a);
+        self.items.insert(a, value_b);
+    }
+
+    fn has(&self, entity: Entity) -> bool {
+        self.items.contains_key(&entity)
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn for_each(&self, f: &mut dyn FnMut(Entity, &u32)) {
+        for (entity, value) in self.items.iter() {
+            f(*entity, value);
+        }
+    }
+
+    fn for_each_mut(&mut self, f: &mut dyn FnMut(Entity, &mut u32)) {
+        for (entity, value) in self.items.iter_mut() {
+            f(*entity, value);
+        }
+    }
+}
+
+#[test]
+fn test_storage_13() {
+    let mut storage = MapStorage::default();
+    for id in [7, 2, 40] {
+        storage.alloc(Entity::new(id));
+    }
+    storage.for_each_mut(&mut |entity, value| *value = entity.id * 10);
+    storage.swap(Entity::new(2), Entity::new(40));
+
+    let mut items = Vec::new();
+    storage.for_each(&mut |entity, value| items.push((entity.id, *value)));
+    items.sort();
+    assert_eq!(items, [(2, 400), (7, 70), (40, 20)]);
+
+    storage.clear();
+    assert!(storage.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "cannot swap u32 of entities 2 and 3: not allocated")]
+fn test_storage_14() {
+    let mut storage = BasicVecStorage::<u32>::default();
+    storage.alloc(Entity::new(2));
+    storage.swap(Entity::new(2), Entity::new(3));
+}
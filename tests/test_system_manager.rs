@@ -1,6 +1,56 @@
-use entity_system::RefreshPeriod;
+use entity_system::{DispatchMode, RefreshPeriod, Resources, System, SystemManager, SystemResult};
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::time::{Duration, Instant};
 
+struct Ping;
+
+entity_system::create_event_adapters!(EventAdapters { Ping });
+type EventDispatcher = entity_system::EventDispatcher<EventAdapters>;
+
+struct CountSystem {
+    name: &'static str,
+    count: u32,
+    refresh: RefreshPeriod,
+    fail: bool,
+    started: bool,
+}
+
+impl CountSystem {
+    fn new(name: &'static str, refresh: RefreshPeriod) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            name,
+            count: 0,
+            refresh,
+            fail: false,
+            started: false,
+        }))
+    }
+}
+
+impl System for CountSystem {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn run(&mut self, _now: Instant, _resources: &Resources) -> SystemResult {
+        self.count += 1;
+        if self.fail {
+            Err("failure".into())
+        } else {
+            Ok(self.refresh)
+        }
+    }
+
+    fn on_start(&mut self) {
+        self.started = true;
+    }
+
+    fn on_stop(&mut self) {
+        self.started = false;
+    }
+}
+
 #[allow(clippy::eq_op)]
 #[test]
 fn test_system_manager_01() {
@@ -21,3 +71,588 @@ fn test_system_manager_01() {
     assert!(RefreshPeriod::At(now) < RefreshPeriod::At(now + Duration::from_secs(5)));
     assert!(RefreshPeriod::At(now + Duration::from_secs(5)) > RefreshPeriod::At(now));
 }
+
+#[test]
+fn test_system_manager_02() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    assert!(system_manager.is_idle());
+
+    let system1 = CountSystem::new("system1", RefreshPeriod::Stop);
+    let system2 = CountSystem::new("system2", RefreshPeriod::EveryTime);
+    system_manager.add_system(Rc::clone(&system1));
+    system_manager.add_system(Rc::clone(&system2));
+    assert!(!system_manager.is_idle());
+
+    assert!(system_manager.update(&event_dispatcher).unwrap() == RefreshPeriod::EveryTime);
+    assert!(!system_manager.is_idle());
+
+    system2.borrow_mut().refresh = RefreshPeriod::Stop;
+    assert!(system_manager.update(&event_dispatcher).unwrap() == RefreshPeriod::Stop);
+    assert!(system_manager.is_idle());
+
+    assert!(system_manager.update(&event_dispatcher).unwrap() == RefreshPeriod::Stop);
+    assert_eq!(system1.borrow().count, 1);
+    assert_eq!(system2.borrow().count, 2);
+}
+
+#[test]
+fn test_system_manager_03() {
+    let mut system_manager = SystemManager::new();
+    system_manager.add_system(CountSystem::new("system1", RefreshPeriod::Stop));
+    assert!(system_manager.get_refresh("system1") == Some(RefreshPeriod::EveryTime));
+    assert!(system_manager.get_refresh("system2").is_none());
+
+    system_manager.set_refresh("system1", RefreshPeriod::Stop);
+    assert!(system_manager.get_refresh("system1") == Some(RefreshPeriod::Stop));
+}
+
+#[test]
+fn test_system_manager_04() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    let system1 = CountSystem::new("system1", RefreshPeriod::EveryTime);
+    let system2 = CountSystem::new("system2", RefreshPeriod::EveryTime);
+    system_manager.add_system(Rc::clone(&system1));
+    system_manager.add_system(Rc::clone(&system2));
+
+    system1.borrow_mut().fail = true;
+    let errors = system_manager.update(&event_dispatcher).err().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].name(), "system1");
+    assert_eq!(errors[0].error().to_string(), "failure");
+    assert_eq!(errors[0].to_string(), "system system1 failed: failure");
+    assert!(system_manager.get_refresh("system1") == Some(RefreshPeriod::Stop));
+    assert_eq!(system2.borrow().count, 1);
+
+    assert!(system_manager.update(&event_dispatcher).unwrap() == RefreshPeriod::EveryTime);
+    assert_eq!(system1.borrow().count, 1);
+    assert_eq!(system2.borrow().count, 2);
+}
+
+#[test]
+fn test_system_manager_05() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    let frame_counter = system_manager.frame_counter();
+    assert_eq!(system_manager.frame(), 0);
+
+    system_manager.add_system(CountSystem::new("system1", RefreshPeriod::EveryTime));
+    system_manager.add_system(CountSystem::new("system2", RefreshPeriod::EveryTime));
+    system_manager.update(&event_dispatcher).unwrap();
+    assert_eq!(system_manager.frame(), 1);
+    system_manager.update(&event_dispatcher).unwrap();
+    assert_eq!(system_manager.frame(), 2);
+    assert_eq!(frame_counter.get(), 2);
+}
+
+#[test]
+fn test_system_manager_06() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    let system1 = CountSystem::new(
+        "system1",
+        RefreshPeriod::At(Instant::now() + Duration::from_millis(10)),
+    );
+    system_manager.add_system(Rc::clone(&system1));
+
+    let start = Instant::now();
+    system_manager
+        .run_until(&event_dispatcher, || system1.borrow().count == 3)
+        .unwrap();
+    assert_eq!(system1.borrow().count, 3);
+    assert!(Instant::now() - start >= Duration::from_millis(10));
+
+    system1.borrow_mut().refresh = RefreshPeriod::Stop;
+    system_manager
+        .run_until(&event_dispatcher, || false)
+        .unwrap();
+    assert_eq!(system1.borrow().count, 4);
+    assert!(system_manager.is_idle());
+}
+
+#[test]
+fn test_system_manager_07() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    let system1 = CountSystem::new("system1", RefreshPeriod::EveryTime);
+    let system2 = CountSystem::new("system2", RefreshPeriod::EveryTime);
+    let system3 = CountSystem::new("system3", RefreshPeriod::EveryTime);
+    system_manager.add_system(Rc::clone(&system1));
+    system_manager.add_system(Rc::clone(&system2));
+    system_manager.add_system(Rc::clone(&system3));
+    assert!(system1.borrow().started);
+    assert!(system2.borrow().started);
+
+    let removed = system_manager.remove_system("system1").unwrap();
+    assert_eq!(removed.borrow().name(), "system1");
+    assert!(!system1.borrow().started);
+    assert!(system_manager.remove_system("system1").is_none());
+
+    system_manager.set_refresh("system3", RefreshPeriod::Stop);
+    system_manager.update(&event_dispatcher).unwrap();
+    assert_eq!(system1.borrow().count, 0);
+    assert_eq!(system2.borrow().count, 1);
+    assert_eq!(system3.borrow().count, 0);
+
+    system_manager.shutdown();
+    assert!(!system2.borrow().started);
+    assert!(!system3.borrow().started);
+    assert!(system_manager.is_idle());
+    assert!(system_manager.get_refresh("system2").is_none());
+}
+
+#[test]
+fn test_system_manager_08() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    let system1 = CountSystem::new("system1", RefreshPeriod::Stop);
+    system_manager.add_system(system1.clone());
+    system_manager.update(&event_dispatcher).unwrap();
+
+    let system2 = CountSystem::new("system1", RefreshPeriod::EveryTime);
+    assert!(system_manager.replace_system(system2.clone()));
+    assert!(!system1.borrow().started);
+    assert!(system2.borrow().started);
+    assert!(system_manager.get_refresh("system1") == Some(RefreshPeriod::Stop));
+
+    system_manager.set_refresh("system1", RefreshPeriod::EveryTime);
+    system_manager.update(&event_dispatcher).unwrap();
+    assert_eq!(system1.borrow().count, 1);
+    assert_eq!(system2.borrow().count, 1);
+
+    let system3 = CountSystem::new("system3", RefreshPeriod::EveryTime);
+    assert!(!system_manager.replace_system(system3.clone()));
+    assert!(!system3.borrow().started);
+}
+
+#[test]
+fn test_system_manager_09() {
+    let before = Instant::now();
+    let refresh = RefreshPeriod::after(Duration::from_secs(1));
+    assert!(refresh > RefreshPeriod::At(before));
+    assert!(refresh <= RefreshPeriod::At(Instant::now() + Duration::from_secs(1)));
+
+    let refresh = RefreshPeriod::in_ms(500);
+    assert!(refresh >= RefreshPeriod::At(before + Duration::from_millis(500)));
+    assert!(refresh <= RefreshPeriod::At(Instant::now() + Duration::from_millis(500)));
+
+    let refresh = RefreshPeriod::in_hz(4.0);
+    assert!(refresh >= RefreshPeriod::At(before + Duration::from_millis(250)));
+    assert!(refresh <= RefreshPeriod::At(Instant::now() + Duration::from_millis(250)));
+}
+
+struct SlowSystem {
+    count: u32,
+}
+
+impl System for SlowSystem {
+    fn name(&self) -> &'static str {
+        "slow"
+    }
+
+    fn run(&mut self, _now: Instant, _resources: &Resources) -> SystemResult {
+        self.count += 1;
+        std::thread::sleep(Duration::from_millis(20));
+        Ok(RefreshPeriod::EveryTime)
+    }
+}
+
+#[test]
+fn test_system_manager_10() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    let slow = Rc::new(RefCell::new(SlowSystem { count: 0 }));
+    let system1 = CountSystem::new("system1", RefreshPeriod::EveryTime);
+    let system2 = CountSystem::new("system2", RefreshPeriod::Stop);
+    system_manager.add_system(slow.clone());
+    system_manager.add_system(system1.clone());
+    system_manager.add_system(system2.clone());
+
+    let budget = Duration::from_millis(5);
+    assert!(!system_manager
+        .update_with_budget(&event_dispatcher, budget)
+        .unwrap());
+    assert_eq!(slow.borrow().count, 1);
+    assert_eq!(system1.borrow().count, 0);
+    assert!(system_manager.get_refresh("system2") == Some(RefreshPeriod::EveryTime));
+
+    system_manager.set_refresh("slow", RefreshPeriod::Stop);
+    assert!(system_manager
+        .update_with_budget(&event_dispatcher, budget)
+        .unwrap());
+    assert_eq!(system1.borrow().count, 1);
+    assert_eq!(system2.borrow().count, 1);
+    assert_eq!(system_manager.frame(), 2);
+}
+
+struct DeltaTime(u32);
+
+struct ResourceSystem;
+
+impl System for ResourceSystem {
+    fn name(&self) -> &'static str {
+        "resource"
+    }
+
+    fn run(&mut self, _now: Instant, resources: &Resources) -> SystemResult {
+        let delta_time = resources.get::<DeltaTime>().ok_or("no delta time")?.0;
+        *resources.get_mut::<u32>().unwrap() += delta_time;
+        Ok(RefreshPeriod::EveryTime)
+    }
+}
+
+#[test]
+fn test_system_manager_11() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    system_manager.add_system(Rc::new(RefCell::new(ResourceSystem)));
+    system_manager.insert_resource(0u32);
+    assert!(system_manager.update(&event_dispatcher).is_err());
+
+    system_manager.set_refresh("resource", RefreshPeriod::EveryTime);
+    system_manager.insert_resource(DeltaTime(3));
+    system_manager.update(&event_dispatcher).unwrap();
+    system_manager.get_resource_mut::<DeltaTime>().unwrap().0 = 2;
+    system_manager.update(&event_dispatcher).unwrap();
+    assert_eq!(*system_manager.get_resource::<u32>().unwrap(), 5);
+    assert!(system_manager.resources().contains::<DeltaTime>());
+}
+
+#[test]
+fn test_system_manager_12() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    system_manager.add_system(Rc::new(RefCell::new(SlowSystem { count: 0 })));
+    system_manager.add_system(CountSystem::new("system1", RefreshPeriod::Stop));
+    assert!(system_manager.timing("slow").is_none());
+    assert!(system_manager.timing("unknown").is_none());
+
+    system_manager.update(&event_dispatcher).unwrap();
+    let timing = system_manager.timing("slow").unwrap();
+    assert!(timing.last >= Duration::from_millis(20));
+    assert_eq!(timing.avg, timing.last);
+    assert!(system_manager.timing("system1").is_some());
+
+    system_manager.update(&event_dispatcher).unwrap();
+    let timing = system_manager.timing("slow").unwrap();
+    assert!(timing.avg >= Duration::from_millis(20));
+
+    system_manager.remove_system("slow");
+    assert!(system_manager.timing("slow").is_none());
+    assert!(system_manager.timing("system1").is_some());
+}
+
+struct LogSystem {
+    name: &'static str,
+    log: Rc<RefCell<Vec<&'static str>>>,
+}
+
+impl System for LogSystem {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn run(&mut self, _now: Instant, _resources: &Resources) -> SystemResult {
+        self.log.borrow_mut().push(self.name);
+        Ok(RefreshPeriod::EveryTime)
+    }
+}
+
+#[test]
+fn test_system_manager_13() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let system = |name| {
+        Rc::new(RefCell::new(LogSystem {
+            name,
+            log: log.clone(),
+        }))
+    };
+    system_manager.add_to_stage("input", system("keyboard"));
+    system_manager.add_to_stage("simulation", system("move"));
+    system_manager.add_to_stage("render", system("draw"));
+    system_manager.add_to_stage("simulation", system("hit"));
+
+    system_manager.update(&event_dispatcher).unwrap();
+    assert_eq!(*log.borrow(), ["keyboard", "move", "hit", "draw"]);
+    assert_eq!(system_manager.frame(), 1);
+
+    log.borrow_mut().clear();
+    system_manager
+        .run_stage("simulation", &event_dispatcher)
+        .unwrap();
+    system_manager
+        .run_stage("simulation", &event_dispatcher)
+        .unwrap();
+    system_manager
+        .run_stage("render", &event_dispatcher)
+        .unwrap();
+    assert_eq!(*log.borrow(), ["move", "hit", "move", "hit", "draw"]);
+    assert_eq!(system_manager.frame(), 1);
+    assert!(
+        system_manager
+            .run_stage("unknown", &event_dispatcher)
+            .unwrap()
+            == RefreshPeriod::Stop
+    );
+
+    log.borrow_mut().clear();
+    system_manager.remove_system("move");
+    system_manager.add_system(system("sound"));
+    system_manager.update(&event_dispatcher).unwrap();
+    assert_eq!(*log.borrow(), ["keyboard", "hit", "draw", "sound"]);
+}
+
+#[test]
+fn test_system_manager_14() {
+    let mut system_manager = SystemManager::new();
+    assert!(system_manager.is_empty());
+    system_manager.add_system(CountSystem::new("system1", RefreshPeriod::Stop));
+    system_manager.add_to_stage("render", CountSystem::new("system2", RefreshPeriod::Stop));
+    system_manager.add_system(CountSystem::new("system3", RefreshPeriod::Stop));
+    assert_eq!(system_manager.len(), 3);
+    assert_eq!(
+        system_manager.system_names().collect::<Vec<_>>(),
+        ["system1", "system2", "system3"]
+    );
+
+    system_manager.remove_system("system2");
+    assert_eq!(system_manager.len(), 2);
+    assert_eq!(
+        system_manager.system_names().collect::<Vec<_>>(),
+        ["system1", "system3"]
+    );
+}
+
+struct PhysicsSystem;
+
+impl System for PhysicsSystem {
+    fn name(&self) -> &'static str {
+        "physics"
+    }
+
+    fn run(&mut self, _now: Instant, _resources: &Resources) -> SystemResult {
+        Ok(RefreshPeriod::Stop)
+    }
+
+    fn required_components(&self) -> &'static [&'static str] {
+        &["Position", "Velocity", "Mass"]
+    }
+}
+
+#[derive(Default)]
+struct Position;
+entity_system::impl_component!(Position);
+
+#[derive(Default)]
+struct Velocity;
+entity_system::impl_component!(Velocity);
+
+#[derive(Default)]
+struct Mass;
+entity_system::impl_component!(Mass);
+
+entity_system::create_entity_manager_component!(MovingEMC { Position, Velocity });
+entity_system::create_entity_manager_component!(PhysicsEMC {
+    Position,
+    Velocity,
+    Mass
+});
+
+#[test]
+fn test_system_manager_15() {
+    let mut system_manager = SystemManager::new();
+    system_manager.add_system(CountSystem::new("system1", RefreshPeriod::Stop));
+    system_manager.add_system(Rc::new(RefCell::new(PhysicsSystem)));
+
+    assert_eq!(
+        system_manager.validate::<MovingEMC>(),
+        [("physics", "Mass")]
+    );
+    assert!(system_manager.validate::<PhysicsEMC>().is_empty());
+}
+
+#[test]
+fn test_system_manager_16() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let system = |name| {
+        Rc::new(RefCell::new(LogSystem {
+            name,
+            log: log.clone(),
+        }))
+    };
+    system_manager.add_system(system("default1"));
+    system_manager.add_system_with_priority(system("low"), -5);
+    system_manager.add_system_with_priority(system("high"), 10);
+    system_manager.add_system(system("default2"));
+    system_manager.add_system_with_priority(system("high2"), 10);
+    system_manager.add_to_stage("render", system("draw"));
+
+    system_manager.update(&event_dispatcher).unwrap();
+    assert_eq!(
+        *log.borrow(),
+        ["high", "high2", "default1", "default2", "low", "draw"]
+    );
+
+    log.borrow_mut().clear();
+    system_manager.remove_system("default1");
+    system_manager.add_system_with_priority(system("mid"), 5);
+    system_manager.update(&event_dispatcher).unwrap();
+    assert_eq!(
+        *log.borrow(),
+        ["high", "high2", "mid", "default2", "low", "draw"]
+    );
+}
+
+struct PingSystem {
+    event_dispatcher: Rc<EventDispatcher>,
+    log: Rc<RefCell<Vec<&'static str>>>,
+}
+
+impl System for PingSystem {
+    fn name(&self) -> &'static str {
+        "ping"
+    }
+
+    fn run(&mut self, _now: Instant, _resources: &Resources) -> SystemResult {
+        self.log.borrow_mut().push("push");
+        self.event_dispatcher.push(Ping);
+        Ok(RefreshPeriod::EveryTime)
+    }
+}
+
+#[test]
+fn test_system_manager_17() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let handler_log = log.clone();
+    let _connection = event_dispatcher.connect_fn(move |_: &Ping| {
+        handler_log.borrow_mut().push("received");
+    });
+    system_manager.add_system(Rc::new(RefCell::new(PingSystem {
+        event_dispatcher: event_dispatcher.clone(),
+        log: log.clone(),
+    })));
+    system_manager.add_system(Rc::new(RefCell::new(LogSystem {
+        name: "log",
+        log: log.clone(),
+    })));
+
+    assert_eq!(system_manager.dispatch_mode(), DispatchMode::AfterEach);
+    system_manager.update(&event_dispatcher).unwrap();
+    assert_eq!(*log.borrow(), ["push", "received", "log"]);
+
+    log.borrow_mut().clear();
+    system_manager.set_dispatch_mode(DispatchMode::AfterAll);
+    system_manager.update(&event_dispatcher).unwrap();
+    assert_eq!(*log.borrow(), ["push", "log", "received"]);
+    assert_eq!(event_dispatcher.pending_count(), 0);
+}
+
+#[test]
+fn test_system_manager_18() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    let system1 = CountSystem::new("system1", RefreshPeriod::Stop);
+    system_manager.add_system(system1.clone());
+    system_manager.update(&event_dispatcher).unwrap();
+    assert!(system_manager.get_refresh("system1") == Some(RefreshPeriod::Stop));
+
+    let system2 = CountSystem::new("system1", RefreshPeriod::Stop);
+    assert!(system_manager.replace_system_reset(system2.clone()));
+    assert!(!system1.borrow().started);
+    assert!(system2.borrow().started);
+    assert!(system_manager.get_refresh("system1") == Some(RefreshPeriod::EveryTime));
+
+    system_manager.update(&event_dispatcher).unwrap();
+    assert_eq!(system1.borrow().count, 1);
+    assert_eq!(system2.borrow().count, 1);
+
+    let system3 = CountSystem::new("system3", RefreshPeriod::EveryTime);
+    assert!(!system_manager.replace_system_reset(system3.clone()));
+    assert!(!system3.borrow().started);
+    assert!(system_manager.get_refresh("system3").is_none());
+}
+
+#[test]
+fn test_system_manager_19() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    let later = CountSystem::new(
+        "later",
+        RefreshPeriod::At(Instant::now() + Duration::from_secs(1)),
+    );
+    let every_time = CountSystem::new("every_time", RefreshPeriod::EveryTime);
+    system_manager.add_system(Rc::clone(&later));
+    system_manager.add_system(Rc::clone(&every_time));
+
+    let start = Instant::now();
+    system_manager
+        .run_until(&event_dispatcher, || every_time.borrow().count == 5)
+        .unwrap();
+    assert!(Instant::now() - start < Duration::from_secs(1));
+    assert_eq!(later.borrow().count, 1);
+}
+
+#[test]
+fn test_system_manager_20() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    let system1 = CountSystem::new("system1", RefreshPeriod::Stop);
+    let system2 = CountSystem::new("system2", RefreshPeriod::Stop);
+    system_manager.add_system(system1.clone());
+    system_manager.add_system(system2.clone());
+
+    assert!(!system_manager
+        .update_with_budget(&event_dispatcher, Duration::ZERO)
+        .unwrap());
+    assert_eq!(system1.borrow().count, 1);
+    assert_eq!(system2.borrow().count, 0);
+
+    assert!(system_manager
+        .update_with_budget(&event_dispatcher, Duration::ZERO)
+        .unwrap());
+    assert_eq!(system1.borrow().count, 1);
+    assert_eq!(system2.borrow().count, 1);
+}
+
+#[test]
+fn test_system_manager_21() {
+    let event_dispatcher = EventDispatcher::new();
+    let mut system_manager = SystemManager::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let system = |name| {
+        Rc::new(RefCell::new(LogSystem {
+            name,
+            log: log.clone(),
+        }))
+    };
+    system_manager.add_system(system("move"));
+    system_manager.add_to_stage("render", system("draw"));
+    system_manager.add_to_stage_with_priority("render", system("sky"), 5);
+    system_manager.add_to_stage_with_priority("render", system("hud"), -5);
+    system_manager.add_to_stage_with_priority("input", system("keyboard"), 1);
+
+    system_manager.update(&event_dispatcher).unwrap();
+    assert_eq!(*log.borrow(), ["move", "sky", "draw", "hud", "keyboard"]);
+}
+
+#[test]
+#[should_panic(expected = "system draw already added")]
+fn test_system_manager_22() {
+    let mut system_manager = SystemManager::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    system_manager.add_system(Rc::new(RefCell::new(LogSystem {
+        name: "draw",
+        log: log.clone(),
+    })));
+    system_manager.add_to_stage(
+        "render",
+        Rc::new(RefCell::new(LogSystem { name: "draw", log })),
+    );
+}
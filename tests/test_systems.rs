@@ -0,0 +1,58 @@
+use entity_system::systems::{Lifetime, LifetimeSystem};
+use entity_system::{create_entity_manager_component, Resources, System};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+create_entity_manager_component!(EMC { Lifetime });
+type EntityManager = entity_system::EntityManager<EMC>;
+
+#[test]
+fn test_systems_01() {
+    let entity_manager = Rc::new(RefCell::new(EntityManager::new()));
+    let e1 = entity_manager.borrow_mut().create_entity();
+    let e2 = entity_manager.borrow_mut().create_entity();
+    let e3 = entity_manager.borrow_mut().create_entity();
+    entity_manager
+        .borrow()
+        .add_component_with::<Lifetime, _>(e1, |lifetime| {
+            *lifetime = Lifetime::new(Duration::from_millis(100))
+        });
+    entity_manager
+        .borrow()
+        .add_component_with::<Lifetime, _>(e2, |lifetime| {
+            *lifetime = Lifetime::new(Duration::from_millis(300))
+        });
+
+    let resources = Resources::new();
+    let mut system = LifetimeSystem::new(Rc::clone(&entity_manager));
+    let now = Instant::now();
+    system.run(now, &resources).unwrap();
+    assert_eq!(
+        entity_manager
+            .borrow()
+            .get_component::<Lifetime>(e1)
+            .remaining,
+        Duration::from_millis(100)
+    );
+
+    system
+        .run(now + Duration::from_millis(150), &resources)
+        .unwrap();
+    assert!(!entity_manager.borrow().is_alive(e1));
+    assert!(entity_manager.borrow().is_alive(e2));
+    assert_eq!(
+        entity_manager
+            .borrow()
+            .get_component::<Lifetime>(e2)
+            .remaining,
+        Duration::from_millis(150)
+    );
+
+    system
+        .run(now + Duration::from_millis(300), &resources)
+        .unwrap();
+    assert!(!entity_manager.borrow().is_alive(e2));
+    assert!(entity_manager.borrow().is_alive(e3));
+    assert_eq!(system.required_components(), ["Lifetime"]);
+}